@@ -0,0 +1,95 @@
+//! Async wrapper over [`Session`] for tests running inside a tokio runtime.
+//!
+//! Every call is forwarded to the blocking session on tokio's blocking thread
+//! pool, so waiting for ticks or events never stalls the async executor.
+
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Duration;
+
+use phase_space_protocol::psip::{EntityRecord, ServerEvent};
+
+use crate::error::{HarnessError, HarnessResult};
+use crate::harness::Session;
+
+/// Session handle whose operations are `async` and safe to await on a tokio runtime.
+pub struct AsyncSession {
+    inner: Arc<Mutex<Option<Session>>>,
+}
+
+impl AsyncSession {
+    /// Wrap a running session.
+    pub fn new(session: Session) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(Some(session))),
+        }
+    }
+
+    /// Run `f` against the underlying session on the blocking thread pool.
+    ///
+    /// This is the escape hatch for session methods without an async mirror.
+    pub async fn with_session<R, F>(&self, f: F) -> HarnessResult<R>
+    where
+        F: FnOnce(&mut Session) -> HarnessResult<R> + Send + 'static,
+        R: Send + 'static,
+    {
+        let inner = self.inner.clone();
+        run_blocking(move || {
+            let mut guard = inner.lock().unwrap_or_else(PoisonError::into_inner);
+            let session = guard.as_mut().ok_or(HarnessError::ConnectionClosed)?;
+            f(session)
+        })
+        .await
+    }
+
+    /// Async mirror of [`Session::advance_ticks`].
+    pub async fn advance_ticks(&self, ticks: u64) -> HarnessResult<()> {
+        self.with_session(move |session| session.advance_ticks(ticks))
+            .await
+    }
+
+    /// Async mirror of [`Session::telemetry_for`].
+    pub async fn telemetry_for(&self, entity_id: u64) -> HarnessResult<Option<EntityRecord>> {
+        self.with_session(move |session| session.telemetry_for(entity_id))
+            .await
+    }
+
+    /// Async mirror of [`Session::wait_for_event`].
+    pub async fn wait_for_event<F>(&self, pred: F, timeout: Duration) -> HarnessResult<ServerEvent>
+    where
+        F: Fn(&ServerEvent) -> bool + Send + 'static,
+    {
+        self.with_session(move |session| session.wait_for_event(pred, timeout))
+            .await
+    }
+
+    /// Async mirror of [`Session::shutdown`].
+    pub async fn shutdown(self) -> HarnessResult<Option<std::process::ExitStatus>> {
+        let inner = self.inner;
+        run_blocking(move || {
+            let session = inner
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .take()
+                .ok_or(HarnessError::ConnectionClosed)?;
+            session.shutdown()
+        })
+        .await
+    }
+}
+
+impl From<Session> for AsyncSession {
+    fn from(session: Session) -> Self {
+        Self::new(session)
+    }
+}
+
+async fn run_blocking<R, F>(f: F) -> HarnessResult<R>
+where
+    F: FnOnce() -> HarnessResult<R> + Send + 'static,
+    R: Send + 'static,
+{
+    match tokio::task::spawn_blocking(f).await {
+        Ok(result) => result,
+        Err(err) => std::panic::resume_unwind(err.into_panic()),
+    }
+}
@@ -0,0 +1,75 @@
+use std::collections::VecDeque;
+
+/// Append-only capture buffer that optionally retains only the newest items.
+///
+/// Items are addressed by absolute position (the number of items pushed before
+/// them), so cursors stay valid after older items are evicted or drained.
+#[derive(Debug)]
+pub(crate) struct CaptureBuffer<T> {
+    items: VecDeque<T>,
+    capacity: Option<usize>,
+    /// Absolute position of the first retained item.
+    offset: u64,
+    dropped: u64,
+}
+
+impl<T> CaptureBuffer<T> {
+    /// Create a buffer; `None` keeps every item.
+    pub(crate) fn new(capacity: Option<usize>) -> Self {
+        Self {
+            items: VecDeque::new(),
+            capacity,
+            offset: 0,
+            dropped: 0,
+        }
+    }
+
+    pub(crate) fn push(&mut self, item: T) {
+        self.items.push_back(item);
+        if let Some(capacity) = self.capacity {
+            while self.items.len() > capacity {
+                self.items.pop_front();
+                self.offset += 1;
+                self.dropped += 1;
+            }
+        }
+    }
+
+    /// Iterate over retained items in insertion order.
+    pub(crate) fn iter(&self) -> impl Iterator<Item = &T> {
+        self.items.iter()
+    }
+
+    /// Iterate over retained items at or after absolute position `from`.
+    pub(crate) fn since(&self, from: u64) -> impl Iterator<Item = (u64, &T)> {
+        let skip = usize::try_from(from.saturating_sub(self.offset)).unwrap_or(usize::MAX);
+        let offset = self.offset;
+        self.items
+            .iter()
+            .enumerate()
+            .skip(skip)
+            .map(move |(index, item)| (offset + index as u64, item))
+    }
+
+    /// Absolute position the next pushed item will get.
+    pub(crate) fn end(&self) -> u64 {
+        self.offset + self.items.len() as u64
+    }
+
+    /// Renumber the retained items so the first one sits at absolute position
+    /// `offset`; only sound while no cursor into this buffer has been handed out.
+    pub(crate) fn rebase(&mut self, offset: u64) {
+        self.offset = offset;
+    }
+
+    /// Remove and return every retained item, leaving the buffer empty.
+    pub(crate) fn drain(&mut self) -> Vec<T> {
+        self.offset += self.items.len() as u64;
+        self.items.drain(..).collect()
+    }
+
+    /// Number of items evicted because the capacity was reached.
+    pub(crate) fn dropped(&self) -> u64 {
+        self.dropped
+    }
+}
@@ -1,9 +1,17 @@
 use std::collections::BTreeMap;
-use std::path::PathBuf;
+use std::fs::File;
+use std::io::BufReader;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
 use std::time::Duration;
 
 use phase_space_protocol::psip::EntityParameters;
 
+use crate::error::{HarnessError, HarnessResult};
+use crate::level::{LevelParser, LogLevel};
+use crate::scenario::{EntitySeed, InputLog};
+use crate::wire::DEFAULT_MAX_FRAME_SIZE;
+
 /// Process-level configuration for launching the engine binary.
 #[derive(Debug, Clone)]
 pub struct EngineConfig {
@@ -13,18 +21,74 @@ pub struct EngineConfig {
     pub extra_args: Vec<String>,
     /// Optional path to a serialized scenario log (passed via `--scenario`).
     pub scenario_path: Option<PathBuf>,
-    /// Optional context plugin to load before ticking.
-    pub context_plugin: Option<PathBuf>,
+    /// Context plugins to load before ticking, in load order.
+    pub context_plugins: Vec<PathBuf>,
+    /// Set when `with_context_plugins` was given an empty list.
+    pub(crate) empty_context_plugins: bool,
     /// Optional deterministic world seed supplied to the engine.
     pub world_seed: Option<u64>,
     /// Extra environment variables applied to the child process.
     pub env: BTreeMap<String, String>,
+    /// Start the child with an empty environment so only `env` is visible.
+    pub clear_env: bool,
     /// Optional working directory override for the child process.
     pub working_directory: Option<PathBuf>,
+    /// Listen address passed via `--bind-addr`; readiness is detected by polling it
+    /// instead of parsing logs.
+    pub bind_addr: Option<SocketAddr>,
+    /// Text preceding the listen address in the engine's startup banner (case-insensitive).
+    pub listen_pattern: String,
     /// Upper bound on how long to wait for the engine to announce its listen address.
     pub startup_timeout: Duration,
     /// Expected delay between engine ticks when no telemetry events are available.
     pub tick_wait: Duration,
+    /// Telemetry silence after which waits fail with `TelemetryStall`; `None` disables the watchdog.
+    pub stall_timeout: Option<Duration>,
+    /// Fail tick waits that see no tick advance instead of falling back to sleeping.
+    pub require_telemetry: bool,
+    /// Poll tick waits at the measured tick interval once enough ticks were observed.
+    pub auto_tick_wait: bool,
+    /// Optional file that every captured log line is appended to as it arrives.
+    pub log_file: Option<PathBuf>,
+    /// Append to `log_file` instead of truncating it; set for auto-restarts.
+    pub(crate) append_log_file: bool,
+    /// Echo captured stdout/stderr lines to the test's stderr as they arrive.
+    pub log_echo: bool,
+    /// Severity detection applied to every captured line.
+    pub(crate) level_parser: LevelParser,
+    /// Maximum number of stdout/stderr lines retained; `None` keeps everything.
+    pub log_capacity: Option<usize>,
+    /// Maximum number of server events retained; `None` keeps everything.
+    pub event_capacity: Option<usize>,
+    /// Drop telemetry events at or before the last tick captured for their entity.
+    pub dedupe_telemetry: bool,
+    /// Store captured lines verbatim instead of trimming surrounding whitespace.
+    pub preserve_log_whitespace: bool,
+    /// Bytes written to the engine's stdin right after spawn.
+    pub stdin: Option<Vec<u8>>,
+    /// Keep stdin open after the initial bytes so tests can feed more input.
+    pub interactive_stdin: bool,
+    /// How long a graceful shutdown may take before the engine is killed.
+    pub shutdown_timeout: Duration,
+    /// How long the engine may take to exit after SIGTERM before it is killed (Unix).
+    pub term_grace: Duration,
+    /// Kill every process the engine started along with it: a process group on
+    /// Unix, a Job Object on Windows (on by default).
+    pub kill_process_group: bool,
+    /// Leave the engine running when the session is dropped instead of shutting it down.
+    pub leak_on_drop: bool,
+    /// How many times to try the initial connection before giving up.
+    pub connect_attempts: u32,
+    /// Delay between initial connection attempts.
+    pub connect_retry_delay: Duration,
+    /// How many times to re-dial a dropped connection before failing; `0` disables it.
+    pub reconnect_attempts: u32,
+    /// Delay between reconnect attempts.
+    pub reconnect_backoff: Duration,
+    /// Response frames longer than this are rejected on pipelined connections.
+    pub max_frame_size: usize,
+    /// How many times `advance_ticks` may relaunch a crashed engine; `0` disables it.
+    pub auto_restart: u32,
 }
 
 impl EngineConfig {
@@ -34,12 +98,39 @@ impl EngineConfig {
             binary_path: binary_path.into(),
             extra_args: Vec::new(),
             scenario_path: None,
-            context_plugin: None,
+            context_plugins: Vec::new(),
+            empty_context_plugins: false,
             world_seed: None,
             env: BTreeMap::new(),
+            clear_env: false,
             working_directory: None,
+            bind_addr: None,
+            listen_pattern: "listening on".to_string(),
             startup_timeout: Duration::from_secs(5),
             tick_wait: Duration::from_millis(10),
+            stall_timeout: None,
+            require_telemetry: false,
+            auto_tick_wait: false,
+            log_file: None,
+            append_log_file: false,
+            log_echo: false,
+            level_parser: LevelParser::default(),
+            log_capacity: None,
+            event_capacity: None,
+            dedupe_telemetry: false,
+            preserve_log_whitespace: false,
+            stdin: None,
+            interactive_stdin: false,
+            shutdown_timeout: Duration::from_secs(2),
+            term_grace: Duration::from_millis(500),
+            kill_process_group: true,
+            leak_on_drop: false,
+            connect_attempts: 1,
+            connect_retry_delay: Duration::from_millis(50),
+            reconnect_attempts: 0,
+            reconnect_backoff: Duration::from_millis(100),
+            max_frame_size: DEFAULT_MAX_FRAME_SIZE,
+            auto_restart: 0,
         }
     }
 
@@ -55,12 +146,38 @@ impl EngineConfig {
         self
     }
 
-    /// Provide a context plugin to load eagerly.
+    /// Provide a single context plugin to load eagerly, replacing any configured before.
     pub fn with_context_plugin(mut self, path: impl Into<PathBuf>) -> Self {
-        self.context_plugin = Some(path.into());
+        self.context_plugins = vec![path.into()];
+        self.empty_context_plugins = false;
+        self
+    }
+
+    /// Load several context plugins, one `--context-plugin` flag each, in the given order.
+    ///
+    /// An empty list makes `EngineHarness::spawn` fail with `HarnessError::Config`.
+    pub fn with_context_plugins(mut self, paths: Vec<PathBuf>) -> Self {
+        self.empty_context_plugins = paths.is_empty();
+        self.context_plugins = paths;
         self
     }
 
+    /// First configured context plugin.
+    #[deprecated(note = "read the `context_plugins` field, which holds every plugin")]
+    pub fn context_plugin(&self) -> Option<&PathBuf> {
+        self.context_plugins.first()
+    }
+
+    /// Reject settings that are inconsistent once the config is complete.
+    pub(crate) fn validate(&self) -> HarnessResult<()> {
+        if self.empty_context_plugins {
+            return Err(HarnessError::Config(
+                "with_context_plugins needs at least one plugin".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
     /// Set a deterministic world seed.
     pub fn with_world_seed(mut self, seed: u64) -> Self {
         self.world_seed = Some(seed);
@@ -73,12 +190,38 @@ impl EngineConfig {
         self
     }
 
+    /// Drop the inherited environment so the engine only sees `with_env` overrides.
+    ///
+    /// Useful for hermetic runs where variables such as `RUST_LOG` from the test
+    /// runner would otherwise leak into the engine.
+    pub fn clear_env(mut self, clear: bool) -> Self {
+        self.clear_env = clear;
+        self
+    }
+
     /// Override the working directory for the spawned process.
     pub fn with_working_directory(mut self, dir: impl Into<PathBuf>) -> Self {
         self.working_directory = Some(dir.into());
         self
     }
 
+    /// Bind the engine to `addr` and wait for it by polling `addr` with TCP
+    /// connects, up to the startup timeout, instead of scanning logs.
+    ///
+    /// Spawning fails if `extra_args` also sets `--bind-addr`. The probe
+    /// connections are closed immediately, so the engine must accept more than
+    /// one connection.
+    pub fn with_bind_addr(mut self, addr: SocketAddr) -> Self {
+        self.bind_addr = Some(addr);
+        self
+    }
+
+    /// Override the banner text that precedes the listen address (default `listening on`).
+    pub fn with_listen_pattern(mut self, pattern: &str) -> Self {
+        self.listen_pattern = pattern.to_string();
+        self
+    }
+
     /// Override the startup timeout used while waiting for the listen address.
     pub fn with_startup_timeout(mut self, timeout: Duration) -> Self {
         self.startup_timeout = timeout;
@@ -90,6 +233,198 @@ impl EngineConfig {
         self.tick_wait = wait;
         self
     }
+
+    /// Fail tick waits with `TelemetryStall` once no telemetry arrived for `timeout`
+    /// while the engine is still running, to tell a hung engine from a slow one.
+    pub fn with_stall_timeout(mut self, timeout: Duration) -> Self {
+        self.stall_timeout = Some(timeout);
+        self
+    }
+
+    /// Make `advance_ticks` fail with `TickTimeout` when telemetry never advances
+    /// the tick, rather than succeeding after the fallback sleep.
+    ///
+    /// Catches engines that are alive but not ticking, or whose telemetry never
+    /// reaches the harness.
+    pub fn require_telemetry(mut self, require: bool) -> Self {
+        self.require_telemetry = require;
+        self
+    }
+
+    /// Poll `advance_ticks` at the engine's measured tick interval instead of
+    /// `tick_wait` once a few tick advances have been observed.
+    ///
+    /// Deadlines are still derived from `tick_wait`.
+    pub fn auto_tick_wait(mut self, auto: bool) -> Self {
+        self.auto_tick_wait = auto;
+        self
+    }
+
+    /// Tee captured log lines into a file live, so they survive a crashed test.
+    pub fn with_log_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.log_file = Some(path.into());
+        self
+    }
+
+    /// Echo every captured stdout/stderr line to the test process's stderr as it
+    /// arrives, prefixed with its stream.
+    ///
+    /// Shows live engine output under `cargo test -- --nocapture`. Setting the
+    /// `PHASE_SPACE_ECHO_LOGS` environment variable to anything but `0` turns
+    /// echoing on without changing the config.
+    pub fn with_log_echo(mut self, echo: bool) -> Self {
+        self.log_echo = echo;
+        self
+    }
+
+    /// Replace the severity detection behind `LogLine::level`.
+    ///
+    /// The default recognizes a leading `[LEVEL]` or `LEVEL:` token; `parser`
+    /// sees each line as captured (event lines included) and returns `None`
+    /// when it cannot tell.
+    pub fn with_level_parser(
+        mut self,
+        parser: impl Fn(&str) -> Option<LogLevel> + Send + Sync + 'static,
+    ) -> Self {
+        self.level_parser = LevelParser::new(parser);
+        self
+    }
+
+    /// Retain only the most recent `max_lines` captured log lines.
+    pub fn with_log_capacity(mut self, max_lines: usize) -> Self {
+        self.log_capacity = Some(max_lines);
+        self
+    }
+
+    /// Retain only the most recent `max_events` server events.
+    ///
+    /// Event queries such as `Session::events` and `Session::wait_for_event`
+    /// only see the retained window.
+    pub fn with_event_capacity(mut self, max_events: usize) -> Self {
+        self.event_capacity = Some(max_events);
+        self
+    }
+
+    /// Drop a telemetry event whose tick is not newer than the last one
+    /// captured for the same entity id, so engines that re-emit telemetry do
+    /// not inflate histories and tick counts.
+    ///
+    /// Only the latest tick per entity is remembered, which relies on the
+    /// engine emitting each entity's telemetry in tick order. The ticks are
+    /// kept across reconnects, so telemetry re-sent on a new connection is
+    /// dropped as well.
+    ///
+    /// Dropped events reach neither the buffer nor subscribers; they are
+    /// counted by `Session::duplicate_telemetry_count`.
+    pub fn dedupe_telemetry(mut self, dedupe: bool) -> Self {
+        self.dedupe_telemetry = dedupe;
+        self
+    }
+
+    /// Keep leading and trailing whitespace on captured lines (trimmed by default).
+    ///
+    /// Only the line terminator (`\n` or `\r\n`) is removed.
+    pub fn preserve_log_whitespace(mut self, preserve: bool) -> Self {
+        self.preserve_log_whitespace = preserve;
+        self
+    }
+
+    /// Pipe `data` into the engine's stdin, closing the pipe once it is written.
+    pub fn with_stdin(mut self, data: Vec<u8>) -> Self {
+        self.stdin = Some(data);
+        self
+    }
+
+    /// Keep the engine's stdin pipe open for `write_stdin` until the session shuts down.
+    pub fn with_interactive_stdin(mut self) -> Self {
+        self.interactive_stdin = true;
+        self
+    }
+
+    /// Override how long to wait for a graceful shutdown before killing the engine.
+    pub fn with_shutdown_timeout(mut self, timeout: Duration) -> Self {
+        self.shutdown_timeout = timeout;
+        self
+    }
+
+    /// On Unix, send SIGTERM to an engine that ignored the shutdown request and
+    /// give it `grace` to exit before escalating to SIGKILL.
+    ///
+    /// Lets the engine flush checkpoints instead of leaving half-written
+    /// artifacts behind. A zero grace skips straight to SIGKILL.
+    pub fn with_term_grace(mut self, grace: Duration) -> Self {
+        self.term_grace = grace;
+        self
+    }
+
+    /// Kill the engine's whole process tree when it is shut down or killed (on
+    /// by default), so worker processes it spawns die with it instead of
+    /// holding on to its port.
+    ///
+    /// On Unix the engine leads its own process group and the group is
+    /// signalled. It thereby leaves the test's process group, so a Ctrl-C in
+    /// the terminal running `cargo test` no longer reaches it directly; it is
+    /// stopped when its session is dropped. On Windows the engine is assigned
+    /// to a Job Object that kills every member when the session closes it;
+    /// processes the engine starts before the assignment, right after spawn,
+    /// are not covered.
+    pub fn kill_process_group(mut self, enabled: bool) -> Self {
+        self.kill_process_group = enabled;
+        self
+    }
+
+    /// Skip the shutdown request and kill when the session is dropped.
+    ///
+    /// Useful for attaching a debugger after a test returns. The caller becomes
+    /// responsible for terminating the process (see `Session::pid`).
+    pub fn leak_on_drop(mut self, leak: bool) -> Self {
+        self.leak_on_drop = leak;
+        self
+    }
+
+    /// Retry the initial connection when the engine's socket is not accepting yet.
+    pub fn with_connect_retry(mut self, attempts: u32, delay: Duration) -> Self {
+        self.connect_attempts = attempts;
+        self.connect_retry_delay = delay;
+        self
+    }
+
+    /// Reject response frames longer than `bytes` with `InvalidData` before
+    /// allocating for them (default 16 MiB).
+    ///
+    /// Applies to the connections the harness decodes itself, i.e. pipelined
+    /// spawns, `Session::inspect_many` and `Session::snapshot`.
+    pub fn with_max_frame_size(mut self, bytes: usize) -> Self {
+        self.max_frame_size = bytes;
+        self
+    }
+
+    /// Re-dial the engine's listen address when the connection drops mid-session.
+    pub fn with_reconnect(mut self, attempts: u32, backoff: Duration) -> Self {
+        self.reconnect_attempts = attempts;
+        self.reconnect_backoff = backoff;
+        self
+    }
+
+    /// Let `advance_ticks` relaunch the engine up to `max_restarts` times when it
+    /// finds the process exited.
+    ///
+    /// The new process is spawned from this same config and seeded again with
+    /// the session's scenario spawns (or re-listed for attached sessions), then
+    /// the wait starts over against the new engine's ticks. Everything the old
+    /// process held in memory is lost unless a scenario file re-seeds it, and
+    /// captured logs and events start over with the new process; the old
+    /// process's output stays available from `Session::previous_runs`, and
+    /// `log_file` is appended to rather than truncated.
+    ///
+    /// Event subscribers, `record_history_for` registrations with their
+    /// samples, and log and event cursors carry over to the new process. The
+    /// restart fails with `UnknownEntity` if a history entity is not respawned
+    /// under the same id.
+    pub fn with_auto_restart(mut self, max_restarts: u32) -> Self {
+        self.auto_restart = max_restarts;
+        self
+    }
 }
 
 /// Minimal scenario description used to seed entities before ticking.
@@ -97,14 +432,112 @@ impl EngineConfig {
 pub struct ScenarioConfig {
     /// Entities to spawn before returning a session handle.
     pub spawns: Vec<SpawnSpec>,
+    /// Keep spawn requests in flight concurrently rather than one round trip each.
+    pub pipelined_spawns: bool,
+    /// Record failed spawns and keep going instead of aborting the scenario.
+    pub continue_on_spawn_error: bool,
+    /// Run `validate` before spawning and refuse to start an invalid scenario.
+    pub strict: bool,
 }
 
 impl ScenarioConfig {
+    /// Load spawn directives from a JSON scenario document (the `--scenario` format).
+    ///
+    /// Each entity's `name` becomes the spawned entity type; `transform`,
+    /// `velocity` and `mass_kg` map onto the spawn parameters.
+    pub fn from_json_file(path: &Path) -> HarnessResult<ScenarioConfig> {
+        let file = File::open(path)?;
+        let document: InputLog = serde_json::from_reader(BufReader::new(file))
+            .map_err(|err| HarnessError::ScenarioParse(format!("{}: {err}", path.display())))?;
+
+        Ok(ScenarioConfig {
+            spawns: document
+                .entities
+                .iter()
+                .map(EntitySeed::to_spawn_spec)
+                .collect(),
+            ..ScenarioConfig::default()
+        })
+    }
+
     /// Add a spawn directive to the scenario.
     pub fn with_spawn(mut self, spec: SpawnSpec) -> Self {
         self.spawns.push(spec);
         self
     }
+
+    /// Add several spawn directives, e.g. the output of `SpawnSpec::replicated`.
+    pub fn with_spawns_iter(mut self, specs: impl IntoIterator<Item = SpawnSpec>) -> Self {
+        self.spawns.extend(specs);
+        self
+    }
+
+    /// Pipeline spawn requests, which speeds up scenarios with many entities.
+    ///
+    /// The requests go over a second connection to the engine, which must
+    /// accept one alongside the harness's own and is not re-dialed if it drops.
+    /// Unless `continue_on_spawn_error` is set, a failed spawn stops further
+    /// requests, but spawns already in flight may still create entities that
+    /// the session does not track.
+    pub fn with_pipelined_spawns(mut self, pipelined: bool) -> Self {
+        self.pipelined_spawns = pipelined;
+        self
+    }
+
+    /// Validate the scenario in `run_scenario` before any entity is spawned.
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Check every spawn directive and report all problems found.
+    ///
+    /// Entity types must be non-empty, position and velocity components finite,
+    /// masses positive when present, and labels unique.
+    pub fn validate(&self) -> Result<(), Vec<String>> {
+        let mut problems = Vec::new();
+        let mut labels = BTreeMap::new();
+        for (index, spec) in self.spawns.iter().enumerate() {
+            let name = format!("spawn #{index} ({:?})", spec.entity_type);
+            if spec.entity_type.trim().is_empty() {
+                problems.push(format!("{name}: entity type is empty"));
+            }
+            if let Some((x, y)) = spec.parameters.position {
+                if !x.is_finite() || !y.is_finite() {
+                    problems.push(format!("{name}: position ({x}, {y}) is not finite"));
+                }
+            }
+            if let Some((x, y)) = spec.parameters.velocity {
+                if !x.is_finite() || !y.is_finite() {
+                    problems.push(format!("{name}: velocity ({x}, {y}) is not finite"));
+                }
+            }
+            if let Some(mass) = spec.parameters.mass {
+                if !(mass > 0.0 && mass.is_finite()) {
+                    problems.push(format!("{name}: mass {mass} must be positive"));
+                }
+            }
+            if let Some(label) = &spec.label {
+                if let Some(first) = labels.insert(label, index) {
+                    problems.push(format!(
+                        "{name}: label {label:?} already used by spawn #{first}"
+                    ));
+                }
+            }
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(problems)
+        }
+    }
+
+    /// Keep spawning after a failure; failures are reported by `Session::failed_spawns`.
+    pub fn continue_on_spawn_error(mut self, continue_on_error: bool) -> Self {
+        self.continue_on_spawn_error = continue_on_error;
+        self
+    }
 }
 
 /// Entity spawn request issued once the engine is reachable.
@@ -113,6 +546,8 @@ pub struct SpawnSpec {
     pub entity_type: String,
     pub parameters: EntityParameters,
     pub dimension: Option<u32>,
+    /// Client-side name used to look the entity up after it is spawned.
+    pub label: Option<String>,
 }
 
 impl SpawnSpec {
@@ -122,6 +557,7 @@ impl SpawnSpec {
             entity_type: entity_type.into(),
             parameters: EntityParameters::default(),
             dimension: None,
+            label: None,
         }
     }
 
@@ -136,4 +572,65 @@ impl SpawnSpec {
         self.dimension = Some(dimension);
         self
     }
+
+    /// Attach a logical label, resolvable through `Session::entity_id_for_label`.
+    pub fn with_label(mut self, label: &str) -> Self {
+        self.label = Some(label.to_string());
+        self
+    }
+
+    /// Expand this spec into `count` copies whose parameters are derived from their index.
+    pub fn replicated(
+        &self,
+        count: usize,
+        parameters: impl Fn(usize) -> EntityParameters,
+    ) -> Vec<SpawnSpec> {
+        (0..count)
+            .map(|index| self.clone().with_parameters(parameters(index)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[allow(deprecated)]
+    fn empty_context_plugin_list_is_a_config_error() {
+        let config = EngineConfig::new("engine").with_context_plugins(Vec::new());
+        assert!(matches!(config.validate(), Err(HarnessError::Config(_))));
+
+        let config = config.with_context_plugin("plugin.so");
+        assert!(config.validate().is_ok());
+        assert_eq!(config.context_plugin(), Some(&PathBuf::from("plugin.so")));
+    }
+
+    #[test]
+    fn validate_reports_every_problem() {
+        let scenario = ScenarioConfig::default()
+            .with_spawn(SpawnSpec::new("probe"))
+            .with_spawn(SpawnSpec::new(" ").with_parameters(EntityParameters {
+                position: Some((f64::NAN, 0.0)),
+                velocity: None,
+                mass: Some(-1.0),
+            }));
+
+        let problems = scenario.validate().expect_err("scenario should be invalid");
+        assert_eq!(problems.len(), 3, "{problems:?}");
+        assert!(problems
+            .iter()
+            .all(|problem| problem.starts_with("spawn #1")));
+    }
+
+    #[test]
+    fn validate_rejects_duplicate_labels() {
+        let scenario = ScenarioConfig::default()
+            .with_spawn(SpawnSpec::new("interceptor").with_label("leader"))
+            .with_spawn(SpawnSpec::new("interceptor").with_label("leader"));
+
+        let problems = scenario.validate().expect_err("labels should clash");
+        assert_eq!(problems.len(), 1);
+        assert!(problems[0].contains("already used by spawn #0"));
+    }
 }
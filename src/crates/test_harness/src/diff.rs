@@ -0,0 +1,141 @@
+//! Field-by-field comparison of two telemetry records for before/after assertions,
+//! and of two entity lists.
+
+use std::collections::HashSet;
+
+use phase_space_protocol::psip::{EntityRecord, EntitySummary};
+
+/// Fields that changed between two records of the same entity.
+///
+/// Deltas are `after - before`; a field is `None` when it is unchanged or
+/// missing from either record.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EntityDiff {
+    pub position_delta: Option<(f64, f64)>,
+    pub velocity_delta: Option<(f64, f64)>,
+    pub mass_delta: Option<f64>,
+    /// `(before, after)` dimension when the entity moved between dimensions.
+    pub dimension_change: Option<(u32, u32)>,
+}
+
+impl EntityDiff {
+    /// Whether no tracked field changed.
+    pub fn is_empty(&self) -> bool {
+        *self == EntityDiff::default()
+    }
+}
+
+/// Compare two records and report which fields changed.
+pub fn record_diff(before: &EntityRecord, after: &EntityRecord) -> EntityDiff {
+    EntityDiff {
+        position_delta: pair_delta(before.position, after.position),
+        velocity_delta: pair_delta(before.velocity, after.velocity),
+        mass_delta: match (before.mass, after.mass) {
+            (Some(before), Some(after)) if before != after => Some(after - before),
+            _ => None,
+        },
+        dimension_change: (before.dimension != after.dimension)
+            .then_some((before.dimension, after.dimension)),
+    }
+}
+
+fn pair_delta(before: Option<(f64, f64)>, after: Option<(f64, f64)>) -> Option<(f64, f64)> {
+    let (before, after) = (before?, after?);
+    (before != after).then_some((after.0 - before.0, after.1 - before.1))
+}
+
+/// Entities that appeared or disappeared between two entity lists.
+#[derive(Debug, Clone, Default)]
+pub struct EntityDelta {
+    /// Entities present only in the newer list, sorted by id.
+    pub added: Vec<EntitySummary>,
+    /// Entities present only in the older list, sorted by id.
+    pub removed: Vec<EntitySummary>,
+}
+
+impl EntityDelta {
+    /// Whether the entity set is unchanged.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
+    }
+}
+
+/// Compare two entity lists by entity id.
+pub(crate) fn entity_delta(before: &[EntitySummary], after: &[EntitySummary]) -> EntityDelta {
+    let only_in = |list: &[EntitySummary], other: &[EntitySummary]| {
+        let ids: HashSet<u64> = other.iter().map(|entity| entity.entity_id).collect();
+        let mut entities: Vec<EntitySummary> = list
+            .iter()
+            .filter(|entity| !ids.contains(&entity.entity_id))
+            .cloned()
+            .collect();
+        entities.sort_by_key(|entity| entity.entity_id);
+        entities
+    };
+
+    EntityDelta {
+        added: only_in(after, before),
+        removed: only_in(before, after),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(position: (f64, f64), velocity: (f64, f64), dimension: u32) -> EntityRecord {
+        EntityRecord {
+            dimension,
+            entity_id: 1,
+            kind: "probe".to_string(),
+            position: Some(position),
+            velocity: Some(velocity),
+            mass: Some(10.0),
+        }
+    }
+
+    #[test]
+    fn reports_only_changed_fields() {
+        let before = record((0.0, 0.0), (1.0, 0.0), 0);
+        let after = record((2.0, 0.5), (1.0, 0.0), 0);
+
+        let diff = record_diff(&before, &after);
+        assert_eq!(diff.position_delta, Some((2.0, 0.5)));
+        assert_eq!(diff.velocity_delta, None);
+        assert_eq!(diff.mass_delta, None);
+        assert_eq!(diff.dimension_change, None);
+        assert!(record_diff(&before, &before).is_empty());
+    }
+
+    #[test]
+    fn reports_dimension_and_mass_changes() {
+        let before = record((0.0, 0.0), (1.0, 0.0), 0);
+        let mut after = record((0.0, 0.0), (0.0, 1.0), 2);
+        after.mass = Some(7.5);
+        after.position = None;
+
+        let diff = record_diff(&before, &after);
+        assert_eq!(diff.position_delta, None);
+        assert_eq!(diff.velocity_delta, Some((-1.0, 1.0)));
+        assert_eq!(diff.mass_delta, Some(-2.5));
+        assert_eq!(diff.dimension_change, Some((0, 2)));
+    }
+
+    #[test]
+    fn entity_delta_reports_added_and_removed_ids() {
+        let summary = |entity_id| EntitySummary {
+            dimension: 0,
+            entity_id,
+            kind: "probe".to_string(),
+            position: None,
+        };
+        let before = vec![summary(1), summary(2), summary(3)];
+        let after = vec![summary(5), summary(1), summary(4), summary(3)];
+
+        let delta = entity_delta(&before, &after);
+        let ids = |list: &[EntitySummary]| list.iter().map(|e| e.entity_id).collect::<Vec<_>>();
+        assert_eq!(ids(&delta.added), vec![4, 5]);
+        assert_eq!(ids(&delta.removed), vec![2]);
+        assert!(entity_delta(&after, &after).is_empty());
+    }
+}
@@ -12,18 +12,73 @@ pub type HarnessResult<T> = Result<T, HarnessError>;
 pub enum HarnessError {
     #[error("failed to spawn engine: {0}")]
     EngineStart(String),
-    #[error("engine terminated early with status {0}")]
-    EngineExited(ExitStatus),
+    #[error("invalid engine config: {0}")]
+    Config(String),
+    #[error(
+        "engine terminated early: {}{}",
+        describe_exit(status),
+        describe_logs(recent_stderr)
+    )]
+    EngineExited {
+        status: ExitStatus,
+        recent_stderr: Vec<String>,
+    },
     #[error("failed to parse listen address from output: {0}")]
     ListenParse(String),
-    #[error("engine did not report a listen address within {0:?}")]
-    StartupTimeout(Duration),
+    #[error("failed to parse scenario {0}")]
+    ScenarioParse(String),
+    #[error("invalid scenario: {}", .0.join("; "))]
+    InvalidScenario(Vec<String>),
+    #[error("engine logged errors: {}", .0.join("; "))]
+    ErrorsLogged(Vec<String>),
+    #[error(
+        "engine did not report a listen address within {timeout:?}{}",
+        describe_logs(recent_logs)
+    )]
+    StartupTimeout {
+        timeout: Duration,
+        recent_logs: Vec<String>,
+    },
+    #[error("engine did not reach tick {target} within {timeout:?} (last saw {observed})")]
+    TickTimeout {
+        target: u64,
+        observed: u64,
+        timeout: Duration,
+    },
+    #[error("engine is running but sent no telemetry for {timeout:?} (last tick {last_tick})")]
+    TelemetryStall { timeout: Duration, last_tick: u64 },
+    #[error("{0} is not supported")]
+    Unsupported(String),
+    #[error("timed out after {timeout:?} waiting for {what}")]
+    WaitTimeout { what: String, timeout: Duration },
     #[error("io error: {0}")]
     Io(#[from] io::Error),
+    #[error("failed to connect to engine after {attempts} attempt(s): {source}")]
+    ConnectFailed {
+        attempts: u32,
+        source: phase_space_protocol::ClientError,
+    },
+    #[error("engine {index} of the pool failed to start: {source}")]
+    PoolSpawn {
+        index: usize,
+        source: Box<HarnessError>,
+    },
     #[error("protocol error: {0}")]
     Protocol(#[from] phase_space_protocol::ClientError),
+    #[error("entity {0} is not known to the session")]
+    UnknownEntity(u64),
+    #[error("entity {entity_id} left bounds {min:?}..={max:?} at tick {tick}: {position:?}")]
+    OutOfBounds {
+        entity_id: u64,
+        tick: u64,
+        position: (f64, f64),
+        min: (f64, f64),
+        max: (f64, f64),
+    },
     #[error("unexpected server response: {0}")]
     UnexpectedResponse(String),
+    #[error("background collector failed: {0}")]
+    CollectorFailed(String),
     #[error("engine connection closed")]
     ConnectionClosed,
 }
@@ -36,4 +91,55 @@ impl HarnessError {
     pub(crate) fn unexpected(message: impl Into<String>) -> Self {
         HarnessError::UnexpectedResponse(message.into())
     }
+
+    /// Exit code of the engine process, if this error reports a normal exit.
+    pub fn exit_code(&self) -> Option<i32> {
+        match self {
+            HarnessError::EngineExited { status, .. } => status.code(),
+            _ => None,
+        }
+    }
+
+    /// Signal that terminated the engine process, if this error reports one (Unix only).
+    pub fn exit_signal(&self) -> Option<i32> {
+        match self {
+            HarnessError::EngineExited { status, .. } => exit_signal(status),
+            _ => None,
+        }
+    }
+}
+
+/// Render an exit status as "exited with code N" or "killed by signal N".
+pub(crate) fn describe_exit(status: &ExitStatus) -> String {
+    if let Some(code) = status.code() {
+        format!("exited with code {code}")
+    } else if let Some(signal) = exit_signal(status) {
+        format!("killed by signal {signal}")
+    } else {
+        format!("exited with status {status}")
+    }
+}
+
+/// Render captured lines as an indented block appended to an error message.
+fn describe_logs(lines: &[String]) -> String {
+    if lines.is_empty() {
+        return String::new();
+    }
+    let mut out = String::from("; recent output:");
+    for line in lines {
+        out.push_str("\n    ");
+        out.push_str(line);
+    }
+    out
+}
+
+#[cfg(unix)]
+fn exit_signal(status: &ExitStatus) -> Option<i32> {
+    use std::os::unix::process::ExitStatusExt;
+    status.signal()
+}
+
+#[cfg(not(unix))]
+fn exit_signal(_status: &ExitStatus) -> Option<i32> {
+    None
 }
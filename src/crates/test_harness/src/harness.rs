@@ -1,9 +1,13 @@
-use std::collections::HashMap;
-use std::io::{BufRead, BufReader};
-use std::net::SocketAddr;
-use std::process::{Child, Command, Stdio};
-use std::sync::atomic::{AtomicU64, Ordering};
-use std::sync::{mpsc, Arc, Mutex};
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::net::{Ipv4Addr, SocketAddr, TcpStream};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::process::{Child, ChildStdin, Command, ExitStatus, Stdio};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{mpsc, Arc, Mutex, OnceLock};
 use std::thread;
 use std::time::{Duration, Instant};
 
@@ -11,9 +15,18 @@ use phase_space_protocol::psip::{
     EntityRecord, EntitySummary, ResponseStatus, ServerEvent, ServerRequest, ServerResponse,
 };
 use phase_space_protocol::Client;
+use regex::Regex;
 
-use crate::config::{EngineConfig, ScenarioConfig};
+use crate::buffer::CaptureBuffer;
+use crate::config::{EngineConfig, ScenarioConfig, SpawnSpec};
+use crate::diff::{entity_delta, EntityDelta};
 use crate::error::{HarnessError, HarnessResult};
+use crate::level::{LevelParser, LogLevel};
+use crate::pipeline;
+use crate::process_tree::ProcessTree;
+use crate::telemetry::TelemetryFields;
+use crate::usage::{self, ResourceUsage};
+use crate::{phase_trace, world_hash};
 
 /// Origin stream for captured log lines.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -23,28 +36,242 @@ pub enum LogStream {
     Event,
 }
 
+impl LogStream {
+    fn label(self) -> &'static str {
+        match self {
+            LogStream::Stdout => "STDOUT",
+            LogStream::Stderr => "STDERR",
+            LogStream::Event => "EVENT",
+        }
+    }
+}
+
 /// Single captured log line with its source.
 #[derive(Debug, Clone)]
 pub struct LogLine {
     pub stream: LogStream,
     pub line: String,
+    /// When the harness read the line (or received the event it was derived from).
+    pub captured_at: Instant,
+    /// Capture order across stdout, stderr and events; increases monotonically.
+    pub seq: u64,
+    level: Option<LogLevel>,
+}
+
+impl LogLine {
+    /// Severity detected when the line was captured, if any.
+    ///
+    /// See `EngineConfig::with_level_parser` for how it is detected.
+    pub fn level(&self) -> Option<LogLevel> {
+        self.level
+    }
+
+    /// Parse the line as a JSON object, for engines that log structured records.
+    ///
+    /// Returns `None` for anything else, including bare JSON scalars and arrays.
+    pub fn as_json(&self) -> Option<serde_json::Value> {
+        if !self.line.trim_start().starts_with('{') {
+            return None;
+        }
+        serde_json::from_str::<serde_json::Value>(&self.line)
+            .ok()
+            .filter(serde_json::Value::is_object)
+    }
+}
+
+impl fmt::Display for LogLine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}] {}", self.stream.label(), self.line)
+    }
+}
+
+/// Aggregate counts over the buffered server events, from `Session::event_stats`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EventStats {
+    /// Number of `Telemetry` events.
+    pub telemetry_events: usize,
+    /// Number of `Log` events.
+    pub log_events: usize,
+    /// Distinct `ship` names seen in telemetry.
+    pub ships: BTreeSet<String>,
+    /// Telemetry event count per `ship` name.
+    pub telemetry_per_ship: BTreeMap<String, usize>,
+}
+
+/// Output captured from an engine that crashed and was relaunched under
+/// `EngineConfig::with_auto_restart`, from `Session::previous_runs`.
+#[derive(Debug, Clone)]
+pub struct PreviousRun {
+    /// How the crashed engine exited.
+    pub status: ExitStatus,
+    /// Every captured log line, as `Session::all_logs` returned it.
+    pub logs: Vec<LogLine>,
+    /// Every buffered server event.
+    pub events: Vec<ServerEvent>,
+    /// Messages of the `Error` responses the engine returned.
+    pub protocol_errors: Vec<String>,
+}
+
+/// Position in the captured stdout/stderr lines, used with `Session::logs_since`.
+///
+/// The default cursor points at the first line ever captured.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LogCursor(u64);
+
+/// Shared file that captured lines are teed into as they arrive.
+type LogSink = Arc<Mutex<File>>;
+
+/// Extra event consumer registered through `Session::subscribe_events`.
+struct EventSubscriber {
+    filter: Box<dyn Fn(&ServerEvent) -> bool + Send>,
+    tx: mpsc::Sender<ServerEvent>,
+}
+
+type EventSubscribers = Arc<Mutex<Vec<EventSubscriber>>>;
+
+/// State shared by the background threads that capture logs and events.
+#[derive(Debug, Default)]
+struct CaptureState {
+    /// First failure reported by a capture thread.
+    failure: OnceLock<String>,
+    /// Next capture sequence number, shared by every stream.
+    sequence: AtomicU64,
+    /// Telemetry silence after which the event collector flags a stall.
+    stall_timeout: Option<Duration>,
+    /// Set while no telemetry arrived within `stall_timeout`.
+    telemetry_stalled: AtomicBool,
+    /// Echo captured process output to the test's stderr.
+    echo_logs: bool,
+    /// Severity detection for captured lines.
+    level_parser: LevelParser,
+    /// Drop telemetry at or below the last tick captured for its entity id.
+    dedupe_telemetry: bool,
+    /// Telemetry events dropped as duplicates.
+    duplicate_telemetry: AtomicU64,
+    /// Highest tick seen in telemetry for each entity id.
+    entity_ticks: Mutex<HashMap<u64, u64>>,
+}
+
+impl CaptureState {
+    /// Stamp a newly captured item with its capture time and sequence number.
+    fn stamp(&self) -> (Instant, u64) {
+        (Instant::now(), self.sequence.fetch_add(1, Ordering::SeqCst))
+    }
+}
+
+/// Records a failure in `CaptureState` if the owning thread unwinds.
+struct PanicGuard {
+    capture: Arc<CaptureState>,
+    name: &'static str,
+}
+
+impl Drop for PanicGuard {
+    fn drop(&mut self) {
+        if thread::panicking() {
+            let _ = self
+                .capture
+                .failure
+                .set(format!("{} thread panicked", self.name));
+        }
+    }
+}
+
+/// Server event together with when it was captured.
+#[derive(Debug, Clone)]
+struct CapturedEvent {
+    event: ServerEvent,
+    captured_at: Instant,
+    seq: u64,
+}
+
+/// Number of recent per-tick intervals kept for `Session::observed_tick_interval`.
+const TICK_INTERVAL_SAMPLES: usize = 32;
+
+/// Intervals required before `EngineConfig::auto_tick_wait` takes effect.
+const TICK_INTERVAL_WARMUP: usize = 4;
+
+/// First and latest `(tick, arrival time)` at which the observed tick advanced,
+/// plus the per-tick intervals between recent advances.
+#[derive(Debug, Default)]
+struct TickWindow {
+    first: Option<(u64, Instant)>,
+    last: Option<(u64, Instant)>,
+    intervals: VecDeque<Duration>,
+}
+
+impl TickWindow {
+    fn record(&mut self, tick: u64, at: Instant) {
+        self.first.get_or_insert((tick, at));
+        if let Some((last_tick, last_at)) = self.last {
+            if tick > last_tick {
+                let ticks = u32::try_from(tick - last_tick).unwrap_or(u32::MAX);
+                self.intervals
+                    .push_back(at.saturating_duration_since(last_at) / ticks);
+                if self.intervals.len() > TICK_INTERVAL_SAMPLES {
+                    self.intervals.pop_front();
+                }
+            }
+        }
+        self.last = Some((tick, at));
+    }
+
+    /// Median of the recent per-tick intervals, once at least `min_samples` exist.
+    fn median_interval(&self, min_samples: usize) -> Option<Duration> {
+        if self.intervals.is_empty() || self.intervals.len() < min_samples {
+            return None;
+        }
+        let mut sorted: Vec<Duration> = self.intervals.iter().copied().collect();
+        sorted.sort_unstable();
+        Some(sorted[sorted.len() / 2])
+    }
+
+    fn rate(&self) -> Option<f64> {
+        let (first_tick, first_at) = self.first?;
+        let (last_tick, last_at) = self.last?;
+        let elapsed = last_at.duration_since(first_at).as_secs_f64();
+        if last_tick <= first_tick || elapsed <= 0.0 {
+            return None;
+        }
+        Some((last_tick - first_tick) as f64 / elapsed)
+    }
 }
 
 /// Handle to a running engine process with an active protocol client.
 pub struct EngineHarness {
-    child: Child,
+    /// The engine process; `None` for an engine reached with `connect`.
+    child: Option<Child>,
     client: Client,
-    log_buffer: Arc<Mutex<Vec<LogLine>>>,
-    event_buffer: Arc<Mutex<Vec<ServerEvent>>>,
+    log_buffer: Arc<Mutex<CaptureBuffer<LogLine>>>,
+    event_buffer: Arc<Mutex<CaptureBuffer<CapturedEvent>>>,
+    event_subscribers: EventSubscribers,
     log_collector: thread::JoinHandle<()>,
     event_collector: thread::JoinHandle<()>,
     max_tick: Arc<AtomicU64>,
+    tick_window: Arc<Mutex<TickWindow>>,
+    capture: Arc<CaptureState>,
     tick_wait: Duration,
+    auto_tick_wait: bool,
+    require_telemetry: bool,
+    address: SocketAddr,
+    log_sink: Option<LogSink>,
+    reconnect_attempts: u32,
+    reconnect_backoff: Duration,
+    stdin_tx: Option<mpsc::Sender<Vec<u8>>>,
+    stdin_writer: Option<thread::JoinHandle<()>>,
+    leak_on_drop: bool,
+    shutdown_timeout: Duration,
+    term_grace: Duration,
+    /// The engine's process group or job, with `EngineConfig::kill_process_group`.
+    process_tree: Option<ProcessTree>,
+    world_seed: Option<u64>,
+    restart_config: Option<EngineConfig>,
+    max_frame_size: usize,
 }
 
 impl EngineHarness {
     /// Spawn the engine process and connect using the synchronous protocol client.
     pub fn spawn(config: EngineConfig) -> HarnessResult<Self> {
+        config.validate()?;
         let mut cmd = Command::new(&config.binary_path);
         let mut args = config.extra_args.clone();
         if let Some(path) = &config.scenario_path {
@@ -55,29 +282,62 @@ impl EngineHarness {
             args.push("--seed".to_string());
             args.push(seed.to_string());
         }
-        if let Some(plugin) = &config.context_plugin {
+        for plugin in &config.context_plugins {
             args.push("--context-plugin".to_string());
             args.push(plugin.display().to_string());
         }
         let has_bind_arg = args
             .iter()
             .any(|arg| arg == "--bind-addr" || arg.starts_with("--bind-addr="));
-        if !has_bind_arg {
+        if let Some(addr) = config.bind_addr {
+            if has_bind_arg {
+                return Err(HarnessError::engine_start(format!(
+                    "--bind-addr passed in extra args conflicts with bind address {addr}"
+                )));
+            }
+            args.push("--bind-addr".to_string());
+            args.push(addr.to_string());
+        } else if !has_bind_arg {
             args.push("--bind-addr".to_string());
             args.push("127.0.0.1:0".to_string());
         }
+        trace_event!(
+            debug,
+            binary = %config.binary_path.display(),
+            ?args,
+            "spawning engine"
+        );
         cmd.args(args);
 
         if let Some(dir) = &config.working_directory {
             cmd.current_dir(dir);
         }
+        if config.clear_env {
+            cmd.env_clear();
+        }
         cmd.envs(&config.env);
+        if config.kill_process_group {
+            ProcessTree::configure(&mut cmd);
+        }
+        let pipe_stdin = config.stdin.is_some() || config.interactive_stdin;
+        if pipe_stdin {
+            cmd.stdin(Stdio::piped());
+        }
         cmd.stdout(Stdio::piped());
         cmd.stderr(Stdio::piped());
 
-        let mut child = cmd
-            .spawn()
-            .map_err(|err| HarnessError::engine_start(err.to_string()))?;
+        let mut child = StartingChild {
+            child: Some(
+                cmd.spawn()
+                    .map_err(|err| HarnessError::engine_start(err.to_string()))?,
+            ),
+            process_tree: None,
+        };
+        if config.kill_process_group {
+            child.process_tree = ProcessTree::adopt(&child).map_err(|err| {
+                HarnessError::engine_start(format!("failed to track the engine's processes: {err}"))
+            })?;
+        }
 
         let stdout = child
             .stdout
@@ -88,91 +348,334 @@ impl EngineHarness {
             .take()
             .ok_or_else(|| HarnessError::engine_start("failed to capture stderr"))?;
 
+        let (stdin_tx, stdin_writer) = match child.stdin.take() {
+            Some(stdin) => {
+                let (tx, rx) = mpsc::channel();
+                let writer = spawn_stdin_writer(stdin, rx);
+                if let Some(data) = &config.stdin {
+                    let _ = tx.send(data.clone());
+                }
+                let tx = config.interactive_stdin.then_some(tx);
+                (tx, Some(writer))
+            }
+            None => (None, None),
+        };
+
+        let log_sink: Option<LogSink> = match &config.log_file {
+            Some(path) if config.append_log_file => Some(Arc::new(Mutex::new(
+                File::options().create(true).append(true).open(path)?,
+            ))),
+            Some(path) => Some(Arc::new(Mutex::new(File::create(path)?))),
+            None => None,
+        };
+
+        let capture = Arc::new(CaptureState {
+            stall_timeout: config.stall_timeout,
+            echo_logs: config.log_echo || echo_logs_from_env(),
+            level_parser: config.level_parser.clone(),
+            dedupe_telemetry: config.dedupe_telemetry,
+            ..CaptureState::default()
+        });
         let (log_tx, log_rx) = mpsc::channel();
-        spawn_log_reader(stdout, LogStream::Stdout, log_tx.clone());
-        spawn_log_reader(stderr, LogStream::Stderr, log_tx);
+        let trim = !config.preserve_log_whitespace;
+        spawn_log_reader(
+            stdout,
+            LogStream::Stdout,
+            log_tx.clone(),
+            log_sink.clone(),
+            trim,
+            capture.clone(),
+        );
+        spawn_log_reader(
+            stderr,
+            LogStream::Stderr,
+            log_tx,
+            log_sink.clone(),
+            trim,
+            capture.clone(),
+        );
+
+        let log_buffer = Arc::new(Mutex::new(CaptureBuffer::new(config.log_capacity)));
+        let (address, reported_seed) = match config.bind_addr {
+            Some(address) => {
+                wait_for_port(
+                    &mut child,
+                    address,
+                    &log_rx,
+                    &log_buffer,
+                    config.startup_timeout,
+                )?;
+                (address, None)
+            }
+            None => wait_for_listen_address(
+                &mut child,
+                &log_rx,
+                &log_buffer,
+                &config.listen_pattern,
+                config.startup_timeout,
+            )?,
+        };
+        trace_event!(debug, pid = child.id(), %address, seed = ?reported_seed, "engine listening");
+        let restart_config = (config.auto_restart > 0).then(|| config.clone());
+        Self::start_client(
+            config,
+            Startup {
+                child: Some(child),
+                address,
+                log_rx,
+                log_buffer,
+                log_sink,
+                capture,
+                stdin_tx,
+                stdin_writer,
+                world_seed: reported_seed,
+                restart_config,
+            },
+        )
+    }
+
+    /// Drive an engine that is already running at `address`, e.g. on another host.
+    ///
+    /// Uses the default `EngineConfig` settings; see `connect_with_config`.
+    pub fn connect(address: SocketAddr) -> HarnessResult<Self> {
+        Self::connect_with_config(address, EngineConfig::new(PathBuf::new()))
+    }
+
+    /// Drive an engine that is already running at `address` with the wait,
+    /// connect and capture settings of `config`.
+    ///
+    /// No process is spawned, so the launch settings (binary, arguments,
+    /// environment, stdin, bind address) are ignored and only events are
+    /// captured, not stdout/stderr. Liveness checks fall back to the connection
+    /// state, and dropping the session disconnects without shutting the engine
+    /// down.
+    pub fn connect_with_config(address: SocketAddr, config: EngineConfig) -> HarnessResult<Self> {
+        let log_sink: Option<LogSink> = match &config.log_file {
+            Some(path) => Some(Arc::new(Mutex::new(File::create(path)?))),
+            None => None,
+        };
+        let capture = Arc::new(CaptureState {
+            stall_timeout: config.stall_timeout,
+            level_parser: config.level_parser.clone(),
+            dedupe_telemetry: config.dedupe_telemetry,
+            ..CaptureState::default()
+        });
+        // No process output: the log collector sees a closed channel and exits.
+        let (_, log_rx) = mpsc::channel();
+        let log_buffer = Arc::new(Mutex::new(CaptureBuffer::new(config.log_capacity)));
+        Self::start_client(
+            config,
+            Startup {
+                child: None,
+                address,
+                log_rx,
+                log_buffer,
+                log_sink,
+                capture,
+                stdin_tx: None,
+                stdin_writer: None,
+                world_seed: None,
+                restart_config: None,
+            },
+        )
+    }
 
-        let log_buffer = Arc::new(Mutex::new(Vec::new()));
-        let address =
-            wait_for_listen_address(&mut child, &log_rx, &log_buffer, config.startup_timeout)?;
-        let log_collector = spawn_log_collector(log_rx, log_buffer.clone());
+    /// Connect the protocol client and start the capture threads.
+    fn start_client(config: EngineConfig, startup: Startup) -> HarnessResult<Self> {
+        let Startup {
+            child,
+            address,
+            log_rx,
+            log_buffer,
+            log_sink,
+            capture,
+            stdin_tx,
+            stdin_writer,
+            world_seed,
+            restart_config,
+        } = startup;
+        let (child, process_tree) = match child.map(StartingChild::into_inner) {
+            Some((child, process_tree)) => (Some(child), process_tree),
+            None => (None, None),
+        };
+        let log_collector = spawn_log_collector(log_rx, log_buffer.clone(), capture.clone());
 
-        let client = Client::connect(address)?;
+        let client =
+            connect_with_retry(address, config.connect_attempts, config.connect_retry_delay)?;
+        trace_event!(debug, %address, "connected to engine");
         let event_rx = client.subscribe();
-        let event_buffer = Arc::new(Mutex::new(Vec::new()));
+        let event_buffer = Arc::new(Mutex::new(CaptureBuffer::new(config.event_capacity)));
         let max_tick = Arc::new(AtomicU64::new(0));
-        let event_collector =
-            spawn_event_collector(event_rx, event_buffer.clone(), max_tick.clone());
+        let tick_window = Arc::new(Mutex::new(TickWindow::default()));
+        let event_subscribers: EventSubscribers = Arc::new(Mutex::new(Vec::new()));
+        let event_collector = spawn_event_collector(
+            event_rx,
+            event_buffer.clone(),
+            event_subscribers.clone(),
+            max_tick.clone(),
+            tick_window.clone(),
+            log_sink.clone(),
+            capture.clone(),
+        );
 
         Ok(Self {
             child,
             client,
             log_buffer,
             event_buffer,
+            event_subscribers,
             log_collector,
             event_collector,
             max_tick,
+            tick_window,
+            capture,
             tick_wait: config.tick_wait,
+            auto_tick_wait: config.auto_tick_wait,
+            require_telemetry: config.require_telemetry,
+            address,
+            log_sink,
+            reconnect_attempts: config.reconnect_attempts,
+            reconnect_backoff: config.reconnect_backoff,
+            stdin_tx,
+            stdin_writer,
+            leak_on_drop: config.leak_on_drop,
+            shutdown_timeout: config.shutdown_timeout,
+            max_frame_size: config.max_frame_size,
+            term_grace: config.term_grace,
+            process_tree,
+            world_seed: world_seed.or(config.world_seed),
+            restart_config,
         })
     }
 
+    /// Queue bytes for the engine's stdin (requires `EngineConfig::with_interactive_stdin`).
+    pub fn write_stdin(&self, bytes: &[u8]) -> HarnessResult<()> {
+        send_stdin(&self.stdin_tx, bytes)
+    }
+
+    /// Protocol client used by the harness, for requests it does not wrap.
+    ///
+    /// Coordinating direct use with the harness's event collector is the
+    /// caller's responsibility.
+    pub fn client(&self) -> &Client {
+        &self.client
+    }
+
     /// Seed the running engine with the provided scenario and return a session handle.
+    ///
+    /// With `ScenarioConfig::with_pipelined_spawns` the spawn requests are written
+    /// in batches over a separate connection instead of waiting for each response
+    /// in turn; entities are still returned in spawn order. The engine must accept
+    /// that second connection, and a spawn that fails stops further requests but
+    /// not those already in flight, so entities spawned after it may exist on the
+    /// engine without being part of the session. With
+    /// `ScenarioConfig::continue_on_spawn_error` failed spawns are recorded in
+    /// `Session::failed_spawns` instead of aborting the scenario. Strict scenarios
+    /// are validated before anything is spawned.
     pub fn run_scenario(self, scenario: ScenarioConfig) -> HarnessResult<Session> {
-        let mut entities = Vec::new();
+        if scenario.strict {
+            scenario.validate().map_err(HarnessError::InvalidScenario)?;
+        }
+        let replay = self.restart_config.is_some().then(|| scenario.clone());
 
-        for spec in scenario.spawns {
-            let response = self.client.send(ServerRequest::Spawn {
-                entity_type: spec.entity_type.clone(),
-                parameters: spec.parameters.clone(),
-                dimension: spec.dimension,
-            })?;
+        let mut protocol_errors = Vec::new();
+        let results = if scenario.pipelined_spawns {
+            let requests = scenario.spawns.iter().map(spawn_request).collect();
+            let continue_on_error = scenario.continue_on_spawn_error;
+            let responses =
+                pipeline::exchange(self.address, requests, self.max_frame_size, |response| {
+                    !continue_on_error && !spawn_succeeded(response)
+                })?;
+            protocol_errors.extend(responses.iter().filter_map(protocol_error));
+            responses
+                .into_iter()
+                .zip(&scenario.spawns)
+                .map(|(response, spec)| spawned_entity(spec, response))
+                .collect()
+        } else {
+            let mut results = Vec::new();
+            for spec in &scenario.spawns {
+                let result = spawn_entity(&self.client, spec, &mut protocol_errors);
+                let failed = result.is_err();
+                results.push(result);
+                if failed && !scenario.continue_on_spawn_error {
+                    break;
+                }
+            }
+            results
+        };
 
-            match response {
-                ServerResponse::Spawned { status, entity } => {
-                    if status != ResponseStatus::Ok {
-                        return Err(HarnessError::unexpected(format!(
-                            "spawn for {} failed with status {status:?}",
-                            spec.entity_type
-                        )));
+        let mut entities = Vec::new();
+        let mut labels = HashMap::new();
+        let mut failed_spawns = Vec::new();
+        for (spec, result) in scenario.spawns.into_iter().zip(results) {
+            match result {
+                Ok(entity) => {
+                    if let Some(label) = spec.label {
+                        labels.insert(label, entity.entity_id);
                     }
-                    entities.push(entity.clone());
-                }
-                ServerResponse::Error { message, .. } => {
-                    return Err(HarnessError::unexpected(message))
+                    entities.push(entity);
                 }
-                other => {
-                    return Err(HarnessError::unexpected(format!(
-                        "spawn returned unexpected response: {other:?}"
-                    )))
+                Err(err) if scenario.continue_on_spawn_error => {
+                    failed_spawns.push((spec, err.to_string()))
                 }
+                Err(err) => return Err(err),
             }
         }
 
-        Ok(self.finish_session(entities))
+        let mut session = self.finish_session(entities);
+        session.labels = labels;
+        session.failed_spawns = failed_spawns;
+        session.protocol_errors = Mutex::new(protocol_errors);
+        if let Some(relaunch) = &mut session.relaunch {
+            relaunch.scenario = replay;
+        }
+        Ok(session)
     }
 
     /// Connect to a pre-seeded engine (e.g., started with `--scenario`) and list existing entities.
     pub fn attach(self) -> HarnessResult<Session> {
-        let response = self.client.send(ServerRequest::List)?;
-        let entities = match response {
-            ServerResponse::Listed { status, entities } => {
-                if status != ResponseStatus::Ok {
-                    return Err(HarnessError::unexpected(format!(
-                        "list failed with status {status:?}"
-                    )));
-                }
-                entities
-            }
-            other => {
-                return Err(HarnessError::unexpected(format!(
-                    "list returned unexpected response: {other:?}"
-                )))
-            }
-        };
-
+        let entities = list_entities(&self.client)?;
         Ok(self.finish_session(entities))
     }
 
+    /// Run `scenario` once per seed, one engine at a time, calling `f` with each session.
+    ///
+    /// Every engine gets its own working directory: `seed-<seed>` under the
+    /// configured working directory, or a temporary directory that is removed
+    /// afterwards. Failures are collected per seed instead of stopping the sweep.
+    pub fn sweep_seeds(
+        config: EngineConfig,
+        seeds: impl IntoIterator<Item = u64>,
+        scenario: ScenarioConfig,
+        mut f: impl FnMut(&mut Session),
+    ) -> Vec<(u64, HarnessResult<()>)> {
+        seeds
+            .into_iter()
+            .map(|seed| {
+                let dir = match &config.working_directory {
+                    Some(base) => Ok((base.join(format!("seed-{seed}")), false)),
+                    None => scratch_dir(&format!("seed-{seed}")).map(|dir| (dir, true)),
+                };
+
+                let result = dir
+                    .map_err(HarnessError::from)
+                    .and_then(|(dir, temporary)| {
+                        let config = config
+                            .clone()
+                            .with_world_seed(seed)
+                            .with_working_directory(&dir);
+                        let result = run_seed(config, scenario.clone(), &mut f);
+                        if temporary {
+                            let _ = std::fs::remove_dir_all(&dir);
+                        }
+                        result
+                    });
+                (seed, result)
+            })
+            .collect()
+    }
+
     fn finish_session(self, entities: Vec<EntitySummary>) -> Session {
         let entity_dimensions = entities
             .iter()
@@ -181,58 +684,378 @@ impl EngineHarness {
 
         Session {
             child: self.child,
-            client: Some(self.client),
+            client: Mutex::new(Some(Arc::new(self.client))),
             log_buffer: self.log_buffer,
             event_buffer: self.event_buffer,
+            event_subscribers: self.event_subscribers,
             log_collector: Some(self.log_collector),
-            event_collector: Some(self.event_collector),
+            event_collectors: Mutex::new(vec![self.event_collector]),
             max_tick: self.max_tick,
+            tick_window: self.tick_window,
+            capture: self.capture,
             tick_wait: self.tick_wait,
+            auto_tick_wait: self.auto_tick_wait,
+            require_telemetry: self.require_telemetry,
+            address: self.address,
+            log_sink: self.log_sink,
+            reconnect_attempts: self.reconnect_attempts,
+            reconnect_backoff: self.reconnect_backoff,
+            reconnect_count: AtomicU64::new(0),
+            stdin_tx: self.stdin_tx,
+            stdin_writer: self.stdin_writer,
+            leak_on_drop: self.leak_on_drop,
+            shutdown_timeout: self.shutdown_timeout,
+            max_frame_size: self.max_frame_size,
+            term_grace: self.term_grace,
+            process_tree: self.process_tree,
+            group_killed: false,
+            world_seed: self.world_seed,
+            relaunch: self.restart_config.map(|config| Relaunch {
+                config,
+                scenario: None,
+            }),
+            restart_count: 0,
+            previous_runs: Vec::new(),
+            event_cursor: AtomicU64::new(0),
+            log_cursor: AtomicU64::new(0),
             entity_dimensions,
             entities,
+            telemetry_history: HashMap::new(),
+            history_entities: BTreeSet::new(),
+            labels: HashMap::new(),
+            failed_spawns: Vec::new(),
+            protocol_errors: Mutex::new(Vec::new()),
+        }
+    }
+}
+
+/// Process-side state handed from `spawn` or `connect` to `start_client`.
+struct Startup {
+    child: Option<StartingChild>,
+    address: SocketAddr,
+    log_rx: mpsc::Receiver<LogLine>,
+    log_buffer: Arc<Mutex<CaptureBuffer<LogLine>>>,
+    log_sink: Option<LogSink>,
+    capture: Arc<CaptureState>,
+    stdin_tx: Option<mpsc::Sender<Vec<u8>>>,
+    stdin_writer: Option<thread::JoinHandle<()>>,
+    world_seed: Option<u64>,
+    restart_config: Option<EngineConfig>,
+}
+
+/// Engine process that has not been handed to a harness yet.
+///
+/// Dropping it kills and reaps the process, along with its process tree, so
+/// a failed startup does not orphan the engine.
+struct StartingChild {
+    child: Option<Child>,
+    process_tree: Option<ProcessTree>,
+}
+
+impl StartingChild {
+    fn into_inner(mut self) -> (Child, Option<ProcessTree>) {
+        let child = self.child.take().expect("child is only taken once");
+        (child, self.process_tree.take())
+    }
+}
+
+impl std::ops::Deref for StartingChild {
+    type Target = Child;
+
+    fn deref(&self) -> &Child {
+        self.child.as_ref().expect("child is only taken once")
+    }
+}
+
+impl std::ops::DerefMut for StartingChild {
+    fn deref_mut(&mut self) -> &mut Child {
+        self.child.as_mut().expect("child is only taken once")
+    }
+}
+
+impl Drop for StartingChild {
+    fn drop(&mut self) {
+        if let Some(mut child) = self.child.take() {
+            trace_event!(
+                debug,
+                pid = child.id(),
+                "killing engine after failed startup"
+            );
+            // Kill the tree while the leader's pid still names its group.
+            if let Some(tree) = &self.process_tree {
+                tree.kill();
+            }
+            let _ = child.kill();
+            let _ = child.wait();
         }
     }
 }
 
+/// How a session relaunches its engine under `EngineConfig::with_auto_restart`.
+struct Relaunch {
+    config: EngineConfig,
+    /// Scenario to seed the new engine with; `None` re-attaches to whatever it loads.
+    scenario: Option<ScenarioConfig>,
+}
+
 /// Active connection to a running engine process plus collected telemetry.
 pub struct Session {
-    child: Child,
-    client: Option<Client>,
-    log_buffer: Arc<Mutex<Vec<LogLine>>>,
-    event_buffer: Arc<Mutex<Vec<ServerEvent>>>,
+    child: Option<Child>,
+    client: Mutex<Option<Arc<Client>>>,
+    log_buffer: Arc<Mutex<CaptureBuffer<LogLine>>>,
+    event_buffer: Arc<Mutex<CaptureBuffer<CapturedEvent>>>,
+    event_subscribers: EventSubscribers,
     log_collector: Option<thread::JoinHandle<()>>,
-    event_collector: Option<thread::JoinHandle<()>>,
+    event_collectors: Mutex<Vec<thread::JoinHandle<()>>>,
     max_tick: Arc<AtomicU64>,
+    tick_window: Arc<Mutex<TickWindow>>,
+    capture: Arc<CaptureState>,
     tick_wait: Duration,
+    auto_tick_wait: bool,
+    require_telemetry: bool,
+    address: SocketAddr,
+    log_sink: Option<LogSink>,
+    reconnect_attempts: u32,
+    reconnect_backoff: Duration,
+    reconnect_count: AtomicU64,
+    stdin_tx: Option<mpsc::Sender<Vec<u8>>>,
+    stdin_writer: Option<thread::JoinHandle<()>>,
+    leak_on_drop: bool,
+    shutdown_timeout: Duration,
+    max_frame_size: usize,
+    term_grace: Duration,
+    /// The engine's process group or job, with `EngineConfig::kill_process_group`.
+    process_tree: Option<ProcessTree>,
+    /// Whether the process tree was already killed.
+    group_killed: bool,
+    world_seed: Option<u64>,
+    relaunch: Option<Relaunch>,
+    restart_count: u32,
+    previous_runs: Vec<PreviousRun>,
+    event_cursor: AtomicU64,
+    log_cursor: AtomicU64,
     entity_dimensions: HashMap<u64, u32>,
     entities: Vec<EntitySummary>,
+    telemetry_history: HashMap<u64, Vec<(u64, EntityRecord)>>,
+    /// Entities inspected after each tick advance, see `record_history_for`.
+    history_entities: BTreeSet<u64>,
+    labels: HashMap<String, u64>,
+    failed_spawns: Vec<(SpawnSpec, String)>,
+    protocol_errors: Mutex<Vec<String>>,
 }
 
 impl Session {
+    /// Return the protocol client, re-dialing the engine if the connection dropped
+    /// and `EngineConfig::with_reconnect` allows it.
+    fn connected_client(&self) -> HarnessResult<Arc<Client>> {
+        let mut guard = self
+            .client
+            .lock()
+            .map_err(|_| HarnessError::ConnectionClosed)?;
+        let current = guard.as_ref().ok_or(HarnessError::ConnectionClosed)?;
+        if current.is_connected() || self.reconnect_attempts == 0 {
+            return Ok(current.clone());
+        }
+
+        let client = Arc::new(self.reconnect()?);
+        *guard = Some(client.clone());
+        Ok(client)
+    }
+
+    fn reconnect(&self) -> HarnessResult<Client> {
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+            match Client::connect(self.address) {
+                Ok(client) => {
+                    let collector = spawn_event_collector(
+                        client.subscribe(),
+                        self.event_buffer.clone(),
+                        self.event_subscribers.clone(),
+                        self.max_tick.clone(),
+                        self.tick_window.clone(),
+                        self.log_sink.clone(),
+                        self.capture.clone(),
+                    );
+                    if let Ok(mut collectors) = self.event_collectors.lock() {
+                        collectors.push(collector);
+                    }
+                    self.reconnect_count.fetch_add(1, Ordering::SeqCst);
+                    return Ok(client);
+                }
+                Err(err) if attempt >= self.reconnect_attempts => return Err(err.into()),
+                Err(_) => thread::sleep(self.reconnect_backoff),
+            }
+        }
+    }
+
+    /// Shared handle to the current protocol client, or `None` once it was released.
+    ///
+    /// The handle is shared because the session may replace its client when it
+    /// reconnects. Events read through `Client::subscribe` are not seen by the
+    /// session's own collector, and coordinating concurrent use with the
+    /// harness is the caller's responsibility.
+    pub fn client(&self) -> Option<Arc<Client>> {
+        self.client.lock().ok()?.clone()
+    }
+
+    /// Report whether the background capture threads are still healthy.
+    ///
+    /// Fails with `CollectorFailed` if a reader or collector thread panicked or
+    /// left a capture buffer poisoned, in which case captured logs and events
+    /// are incomplete.
+    pub fn collector_health(&self) -> HarnessResult<()> {
+        if let Some(reason) = self.capture.failure.get() {
+            return Err(HarnessError::CollectorFailed(reason.clone()));
+        }
+        if self.log_buffer.is_poisoned() || self.event_buffer.is_poisoned() {
+            return Err(HarnessError::CollectorFailed(
+                "capture buffer lock poisoned".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Number of times the session transparently re-dialed the engine.
+    pub fn reconnect_count(&self) -> u64 {
+        self.reconnect_count.load(Ordering::SeqCst)
+    }
+
+    /// Number of times `advance_ticks` relaunched a crashed engine.
+    pub fn restart_count(&self) -> u32 {
+        self.restart_count
+    }
+
+    /// Output of every engine this session replaced after a crash, oldest
+    /// first. The accessors above only cover the current engine.
+    pub fn previous_runs(&self) -> &[PreviousRun] {
+        &self.previous_runs
+    }
+
+    /// OS process id of the engine, or `None` for a remote engine reached with
+    /// `EngineHarness::connect`.
+    pub fn pid(&self) -> Option<u32> {
+        self.child.as_ref().map(Child::id)
+    }
+
+    /// Sample the engine process's resident memory and CPU time.
+    ///
+    /// Only implemented on Linux, where it reads `/proc/<pid>`; fails with
+    /// `Unsupported` elsewhere and for remote engines.
+    pub fn resource_usage(&self) -> HarnessResult<ResourceUsage> {
+        let pid = self.pid().ok_or_else(|| {
+            HarnessError::Unsupported("resource usage of a remote engine".to_string())
+        })?;
+        usage::sample(pid)
+    }
+
+    /// Exit status of the engine process, if it has exited.
+    ///
+    /// With a process group, the group is killed while the exited leader is
+    /// still unreaped, so its id cannot have been reused yet.
+    fn try_exit(&mut self) -> HarnessResult<Option<ExitStatus>> {
+        let Some(child) = &self.child else {
+            return Ok(None);
+        };
+        if self.process_tree.is_some() && leader_exited(child)? {
+            // Reap workers the engine left behind in its group.
+            self.kill_group();
+        }
+        match &mut self.child {
+            Some(child) => Ok(child.try_wait()?),
+            None => Ok(None),
+        }
+    }
+
+    /// Fail with `EngineExited` if the engine process died or, for a remote
+    /// engine, with `ConnectionClosed` if the connection dropped.
+    fn ensure_running(&mut self) -> HarnessResult<()> {
+        if let Some(status) = self.try_exit()? {
+            return Err(self.engine_exited(status));
+        }
+        if self.child.is_none() && !self.connected_client()?.is_connected() {
+            return Err(HarnessError::ConnectionClosed);
+        }
+        Ok(())
+    }
+
+    /// World seed the engine reported in a `seed=N` line before it started
+    /// listening, or the configured seed if it printed none.
+    ///
+    /// Lines are only scanned while waiting for the listen address, so with a
+    /// bind address set this is always the configured seed.
+    pub fn world_seed(&self) -> Option<u64> {
+        self.world_seed
+    }
+
     /// Return the entities created during scenario setup.
     pub fn entities(&self) -> &[EntitySummary] {
         &self.entities
     }
 
+    /// First cached entity of the given kind.
+    pub fn find_by_kind(&self, kind: &str) -> Option<&EntitySummary> {
+        self.entities.iter().find(|entity| entity.kind == kind)
+    }
+
+    /// Every cached entity of the given kind, in cache order.
+    pub fn find_all_by_kind(&self, kind: &str) -> Vec<&EntitySummary> {
+        self.entities
+            .iter()
+            .filter(|entity| entity.kind == kind)
+            .collect()
+    }
+
+    /// Group the cached entities by dimension, each group sorted by entity id.
+    pub fn entities_by_dimension(&self) -> BTreeMap<u32, Vec<EntitySummary>> {
+        let mut groups: BTreeMap<u32, Vec<EntitySummary>> = BTreeMap::new();
+        for entity in &self.entities {
+            groups
+                .entry(entity.dimension)
+                .or_default()
+                .push(entity.clone());
+        }
+        for group in groups.values_mut() {
+            group.sort_by_key(|entity| entity.entity_id);
+        }
+        groups
+    }
+
+    /// Distinct dimensions of the cached entities, in ascending order.
+    pub fn dimensions(&self) -> Vec<u32> {
+        let mut dimensions: Vec<u32> = self.entities.iter().map(|e| e.dimension).collect();
+        dimensions.sort_unstable();
+        dimensions.dedup();
+        dimensions
+    }
+
+    /// Spawn an additional entity into the running engine and cache its summary.
+    pub fn spawn(&mut self, spec: SpawnSpec) -> HarnessResult<EntitySummary> {
+        let entity = spawned_entity(&spec, self.send(spawn_request(&spec))?)?;
+        self.entity_dimensions
+            .insert(entity.entity_id, entity.dimension);
+        if let Some(label) = spec.label {
+            self.labels.insert(label, entity.entity_id);
+        }
+        self.entities.push(entity.clone());
+        Ok(entity)
+    }
+
+    /// Resolve a label assigned with `SpawnSpec::with_label` to its entity id.
+    pub fn entity_id_for_label(&self, label: &str) -> Option<u64> {
+        self.labels.get(label).copied()
+    }
+
+    /// Return the spawns that failed during scenario setup, with their error messages.
+    ///
+    /// Only populated when `ScenarioConfig::continue_on_spawn_error` is set.
+    pub fn failed_spawns(&self) -> &[(SpawnSpec, String)] {
+        &self.failed_spawns
+    }
+
     /// Refresh the cached entity list using a list request.
     pub fn refresh_entities(&mut self) -> HarnessResult<&[EntitySummary]> {
-        let client = self.client.as_ref().ok_or(HarnessError::ConnectionClosed)?;
-        let response = client.send(ServerRequest::List)?;
-        let entities = match response {
-            ServerResponse::Listed { status, entities } => {
-                if status != ResponseStatus::Ok {
-                    return Err(HarnessError::unexpected(format!(
-                        "list failed with status {status:?}"
-                    )));
-                }
-                entities
-            }
-            other => {
-                return Err(HarnessError::unexpected(format!(
-                    "list returned unexpected response: {other:?}"
-                )))
-            }
-        };
+        let entities = listed_entities(self.send(ServerRequest::List)?)?;
 
         self.entity_dimensions.clear();
         for entity in &entities {
@@ -244,73 +1067,849 @@ impl Session {
         Ok(&self.entities)
     }
 
+    /// Poll the entity list until at least `count` entities exist.
+    ///
+    /// Fails with `EngineExited` if the process dies and `WaitTimeout` if the
+    /// count is not reached within `timeout`.
+    pub fn wait_for_entity_count(
+        &mut self,
+        count: usize,
+        timeout: Duration,
+    ) -> HarnessResult<&[EntitySummary]> {
+        let start = Instant::now();
+        loop {
+            self.ensure_running()?;
+            if self.refresh_entities()?.len() >= count {
+                return Ok(&self.entities);
+            }
+            if start.elapsed() >= timeout {
+                return Err(HarnessError::WaitTimeout {
+                    what: format!("{count} entities (last saw {})", self.entities.len()),
+                    timeout,
+                });
+            }
+            thread::sleep(self.tick_wait);
+        }
+    }
+
+    /// Refresh the cached entity list and report which entities appeared or
+    /// disappeared since the previous cache.
+    pub fn refresh_entities_diff(&mut self) -> HarnessResult<EntityDelta> {
+        let previous = self.entities.clone();
+        let current = self.refresh_entities()?;
+        Ok(entity_delta(&previous, current))
+    }
+
     /// Wait for the engine to progress by a number of ticks.
     ///
     /// If telemetry events are observed, this waits until the requested tick delta
     /// is reached. Otherwise it sleeps for a conservative fallback duration while
-    /// ensuring the engine is still alive.
+    /// ensuring the engine is still alive; with `EngineConfig::require_telemetry`
+    /// a wait that saw no tick advance fails with `TickTimeout` instead. With
+    /// `EngineConfig::with_auto_restart` a crashed engine is relaunched and the
+    /// wait restarts against it. Afterwards the entities registered with
+    /// `record_history_for` are inspected and recorded in their telemetry history.
     pub fn advance_ticks(&mut self, ticks: u64) -> HarnessResult<()> {
+        self.advance_ticks_timeout(ticks, self.tick_deadline(ticks))
+    }
+
+    /// Like `advance_ticks`, but wait up to `timeout` instead of the deadline
+    /// derived from `tick_wait`.
+    pub fn advance_ticks_timeout(&mut self, ticks: u64, timeout: Duration) -> HarnessResult<()> {
         if ticks == 0 {
             return Ok(());
         }
 
-        let start_tick = self.max_tick.load(Ordering::SeqCst);
-        let target_tick = start_tick.saturating_add(ticks);
-        let mut waited = Duration::ZERO;
-        let tick_scale = u32::try_from(ticks.max(1)).unwrap_or(u32::MAX);
-        let deadline = self.tick_wait.saturating_mul(tick_scale).saturating_mul(2);
-
-        while waited <= deadline {
-            if let Some(status) = self.child.try_wait()? {
-                return Err(HarnessError::EngineExited(status));
-            }
-
-            if self.max_tick.load(Ordering::SeqCst) >= target_tick {
-                return Ok(());
-            }
+        self.wait_ticks(ticks, timeout)?;
+        self.record_history()
+    }
 
-            thread::sleep(self.tick_wait);
-            waited += self.tick_wait;
+    /// Like `advance_ticks`, but return how many ticks the engine actually advanced.
+    ///
+    /// The count may exceed `ticks` when telemetry jumps, and is whatever was
+    /// observed (possibly `0`) when telemetry stays silent until the deadline.
+    pub fn advance_ticks_counted(&mut self, ticks: u64) -> HarnessResult<u64> {
+        if ticks == 0 {
+            return Ok(0);
         }
 
-        // Fallback when telemetry is silent: still verify the process is running.
-        if let Some(status) = self.child.try_wait()? {
-            return Err(HarnessError::EngineExited(status));
-        }
-        if let Some(client) = &self.client {
-            if !client.is_connected() {
+        let advanced = self.wait_ticks(ticks, self.tick_deadline(ticks))?;
+        self.record_history()?;
+        Ok(advanced)
+    }
+
+    /// Let the engine run for `duration` of wall-clock time and return how many
+    /// ticks it advanced meanwhile.
+    ///
+    /// Fails with `EngineExited` or `ConnectionClosed` if the engine dies or the
+    /// connection drops during the window.
+    pub fn advance_real_time(&mut self, duration: Duration) -> HarnessResult<u64> {
+        let start_tick = self.max_tick.load(Ordering::SeqCst);
+        let start = Instant::now();
+        loop {
+            self.ensure_running()?;
+            if !self.connected_client()?.is_connected() {
                 return Err(HarnessError::ConnectionClosed);
             }
-        } else {
-            return Err(HarnessError::ConnectionClosed);
+
+            let remaining = match duration.checked_sub(start.elapsed()) {
+                Some(remaining) if !remaining.is_zero() => remaining,
+                _ => break,
+            };
+            thread::sleep(remaining.min(self.tick_wait));
         }
 
-        Ok(())
+        Ok(self
+            .max_tick
+            .load(Ordering::SeqCst)
+            .saturating_sub(start_tick))
     }
 
-    /// Fetch the latest telemetry for an entity using an inspect request.
-    pub fn telemetry_for(&self, entity_id: u64) -> HarnessResult<Option<EntityRecord>> {
-        let dimension = match self.entity_dimensions.get(&entity_id) {
-            Some(dimension) => *dimension,
-            None => return Ok(None),
-        };
-
-        let client = self.client.as_ref().ok_or(HarnessError::ConnectionClosed)?;
-        let response = client.send(ServerRequest::Inspect {
-            dimension,
-            entity_id,
-        })?;
+    /// Default deadline for advancing `ticks`: twice the expected tick time.
+    fn tick_deadline(&self, ticks: u64) -> Duration {
+        let tick_scale = u32::try_from(ticks.max(1)).unwrap_or(u32::MAX);
+        self.tick_wait.saturating_mul(tick_scale).saturating_mul(2)
+    }
 
-        match response {
-            ServerResponse::InspectResult { entity, .. } => Ok(entity),
-            other => Err(HarnessError::unexpected(format!(
-                "inspect returned unexpected response: {other:?}"
-            ))),
+    /// Wait up to `deadline` for `ticks` more ticks, relaunching a crashed engine
+    /// when `EngineConfig::with_auto_restart` allows it.
+    fn wait_ticks(&mut self, ticks: u64, deadline: Duration) -> HarnessResult<u64> {
+        loop {
+            match self.wait_ticks_once(ticks, deadline) {
+                Err(HarnessError::EngineExited { status, .. }) if self.can_restart() => {
+                    self.restart(status)?
+                }
+                result => return result,
+            }
         }
     }
 
-    /// Return all captured log lines for an entity id (matching telemetry events and stdout).
-    pub fn logs_for(&self, entity_id: u64) -> Vec<LogLine> {
+    fn can_restart(&self) -> bool {
+        self.relaunch
+            .as_ref()
+            .is_some_and(|relaunch| self.restart_count < relaunch.config.auto_restart)
+    }
+
+    /// Replace the exited engine with a fresh process seeded the same way,
+    /// keeping the crashed engine's output in `previous_runs`.
+    fn restart(&mut self, status: ExitStatus) -> HarnessResult<()> {
+        let Some(relaunch) = &self.relaunch else {
+            return Ok(());
+        };
+        trace_event!(
+            info,
+            restarts = self.restart_count + 1,
+            "restarting crashed engine"
+        );
+        let mut config = relaunch.config.clone();
+        config.append_log_file = true;
+        let harness = EngineHarness::spawn(config)?;
+        let mut fresh = match relaunch.scenario.clone() {
+            Some(scenario) => harness.run_scenario(scenario)?,
+            None => harness.attach()?,
+        };
+        fresh.take_over(self)?;
+        fresh.restart_count = self.restart_count + 1;
+        fresh.previous_runs = std::mem::take(&mut self.previous_runs);
+        fresh.previous_runs.push(PreviousRun {
+            status,
+            logs: self.all_logs(),
+            events: self.events(),
+            protocol_errors: self.protocol_errors(),
+        });
+        // Dropping the old session reaps the exited process and its threads.
+        std::mem::swap(self, &mut fresh);
+        Ok(())
+    }
+
+    /// Carry the test's registrations over from the crashed session: history
+    /// entities and their samples, event subscribers, and the `wait_for_*` and
+    /// `LogCursor` positions, which continue after the crashed run's output.
+    ///
+    /// Fails with `UnknownEntity` if a history entity was not respawned.
+    fn take_over(&mut self, previous: &mut Session) -> HarnessResult<()> {
+        if let Some(missing) = previous
+            .history_entities
+            .iter()
+            .find(|entity_id| !self.entity_dimensions.contains_key(entity_id))
+        {
+            return Err(HarnessError::UnknownEntity(*missing));
+        }
+        self.history_entities = std::mem::take(&mut previous.history_entities);
+        self.telemetry_history = std::mem::take(&mut previous.telemetry_history);
+
+        let poisoned = |what: &str| HarnessError::CollectorFailed(format!("{what} lock poisoned"));
+        let subscribers = std::mem::take(
+            &mut *previous
+                .event_subscribers
+                .lock()
+                .map_err(|_| poisoned("event subscriber"))?,
+        );
+        self.event_subscribers
+            .lock()
+            .map_err(|_| poisoned("event subscriber"))?
+            .extend(subscribers);
+
+        let log_end = previous
+            .log_buffer
+            .lock()
+            .map_err(|_| poisoned("log buffer"))?
+            .end();
+        self.log_buffer
+            .lock()
+            .map_err(|_| poisoned("log buffer"))?
+            .rebase(log_end);
+        let event_end = previous
+            .event_buffer
+            .lock()
+            .map_err(|_| poisoned("event buffer"))?
+            .end();
+        self.event_buffer
+            .lock()
+            .map_err(|_| poisoned("event buffer"))?
+            .rebase(event_end);
+        self.log_cursor = AtomicU64::new(previous.log_cursor.load(Ordering::SeqCst));
+        self.event_cursor = AtomicU64::new(previous.event_cursor.load(Ordering::SeqCst));
+        Ok(())
+    }
+
+    /// Wait up to `deadline` for `ticks` more ticks and return the observed advance.
+    fn wait_ticks_once(&mut self, ticks: u64, deadline: Duration) -> HarnessResult<u64> {
+        let start_tick = self.max_tick.load(Ordering::SeqCst);
+        let target_tick = start_tick.saturating_add(ticks);
+        let poll = self.poll_interval();
+        let mut waited = Duration::ZERO;
+
+        while waited <= deadline {
+            self.ensure_running()?;
+            self.collector_health()?;
+            self.check_telemetry_stall()?;
+
+            let observed = self.max_tick.load(Ordering::SeqCst);
+            if observed >= target_tick {
+                return Ok(observed - start_tick);
+            }
+
+            thread::sleep(poll);
+            waited += poll;
+        }
+
+        // Fallback when telemetry is silent: still verify the process is running.
+        self.ensure_running()?;
+        if !self.connected_client()?.is_connected() {
+            return Err(HarnessError::ConnectionClosed);
+        }
+
+        let observed = self.max_tick.load(Ordering::SeqCst);
+        if self.require_telemetry && observed == start_tick {
+            return Err(HarnessError::TickTimeout {
+                target: target_tick,
+                observed,
+                timeout: deadline,
+            });
+        }
+        Ok(observed - start_tick)
+    }
+
+    /// Sleep granularity for tick waits: the measured tick interval with
+    /// `EngineConfig::auto_tick_wait` once warmed up, `tick_wait` otherwise.
+    fn poll_interval(&self) -> Duration {
+        if !self.auto_tick_wait {
+            return self.tick_wait;
+        }
+        self.tick_window
+            .lock()
+            .ok()
+            .and_then(|window| window.median_interval(TICK_INTERVAL_WARMUP))
+            .filter(|interval| !interval.is_zero())
+            .unwrap_or(self.tick_wait)
+    }
+
+    /// Fail with `TelemetryStall` if the event collector's watchdog fired.
+    fn check_telemetry_stall(&self) -> HarnessResult<()> {
+        match self.capture.stall_timeout {
+            Some(timeout) if self.capture.telemetry_stalled.load(Ordering::SeqCst) => {
+                Err(HarnessError::TelemetryStall {
+                    timeout,
+                    last_tick: self.max_tick.load(Ordering::SeqCst),
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Inspect `entity_ids` after every tick advance and keep the samples in
+    /// their `telemetry_history`.
+    ///
+    /// Recording costs one inspect per registered entity per advance, and a
+    /// failed inspect fails the advance, so only entities that are asserted on
+    /// should be registered. Fails with `UnknownEntity` for ids the session
+    /// does not know, registering none of them.
+    pub fn record_history_for(&mut self, entity_ids: &[u64]) -> HarnessResult<()> {
+        if let Some(unknown) = entity_ids
+            .iter()
+            .find(|entity_id| !self.entity_dimensions.contains_key(entity_id))
+        {
+            return Err(HarnessError::UnknownEntity(*unknown));
+        }
+        self.history_entities.extend(entity_ids);
+        Ok(())
+    }
+
+    fn record_history(&mut self) -> HarnessResult<()> {
+        let tick = self.max_tick.load(Ordering::SeqCst);
+        let entity_ids: Vec<u64> = self.history_entities.iter().copied().collect();
+        for entity_id in entity_ids {
+            if let Some(record) = self.telemetry_for(entity_id)? {
+                let samples = self.telemetry_history.entry(entity_id).or_default();
+                if samples.last().map(|(last, _)| *last) == Some(tick) {
+                    samples.pop();
+                }
+                samples.push((tick, record));
+            }
+        }
+        Ok(())
+    }
+
+    /// Highest tick reported by engine telemetry so far.
+    pub fn current_tick(&self) -> u64 {
+        self.max_tick.load(Ordering::SeqCst)
+    }
+
+    /// Ticks per second between the first and latest observed tick advance.
+    ///
+    /// Returns `None` until the tick has advanced at least twice.
+    pub fn estimated_tick_rate(&self) -> Option<f64> {
+        self.tick_window.lock().ok()?.rate()
+    }
+
+    /// Median wall-clock time per tick over recent tick advances.
+    ///
+    /// Returns `None` until the tick has advanced at least twice.
+    pub fn observed_tick_interval(&self) -> Option<Duration> {
+        self.tick_window.lock().ok()?.median_interval(1)
+    }
+
+    /// Return the `(tick, record)` samples recorded for an entity by `advance_ticks`.
+    ///
+    /// Empty unless the entity was registered with `record_history_for`.
+    pub fn telemetry_history(&self, entity_id: u64) -> Vec<(u64, EntityRecord)> {
+        self.telemetry_history
+            .get(&entity_id)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Return the `(tick, position)` path of an entity from its telemetry history.
+    ///
+    /// Samples are those taken by `advance_ticks`, in tick order; samples
+    /// without a position are omitted.
+    pub fn trajectory(&self, entity_id: u64) -> Vec<(u64, (f64, f64))> {
+        self.telemetry_history
+            .get(&entity_id)
+            .map(|samples| {
+                samples
+                    .iter()
+                    .filter_map(|(tick, record)| Some((*tick, record.position?)))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Velocity implied by the last two positions in the entity's `trajectory`.
+    ///
+    /// The displacement is divided by the ticks between the samples times
+    /// `dt`, the scenario's seconds per tick. Returns `None` with fewer than
+    /// two positioned samples.
+    pub fn observed_velocity(&self, entity_id: u64, dt: f64) -> Option<(f64, f64)> {
+        let trajectory = self.trajectory(entity_id);
+        match trajectory.as_slice() {
+            [.., previous, last] => finite_difference(*previous, *last, dt),
+            _ => None,
+        }
+    }
+
+    /// Fail with `OutOfBounds` at the first `trajectory` sample of an entity
+    /// outside the box from `min` to `max` (inclusive).
+    ///
+    /// Only samples recorded by `advance_ticks` are checked, so an entity with
+    /// no positioned samples passes. Fails with `UnknownEntity` for ids the
+    /// session does not know.
+    pub fn assert_within_bounds(
+        &self,
+        entity_id: u64,
+        min: (f64, f64),
+        max: (f64, f64),
+    ) -> HarnessResult<()> {
+        if !self.entity_dimensions.contains_key(&entity_id) {
+            return Err(HarnessError::UnknownEntity(entity_id));
+        }
+        let violation = self
+            .trajectory(entity_id)
+            .into_iter()
+            .find(|(_, (x, y))| !((min.0..=max.0).contains(x) && (min.1..=max.1).contains(y)));
+        match violation {
+            Some((tick, position)) => Err(HarnessError::OutOfBounds {
+                entity_id,
+                tick,
+                position,
+                min,
+                max,
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Block until the engine reports an absolute tick and return the tick observed.
+    ///
+    /// Unlike `advance_ticks`, the target does not depend on the tick seen at call
+    /// time. Fails with `EngineExited` if the process dies and `TickTimeout` if the
+    /// tick is not reached within `timeout`.
+    pub fn wait_for_tick(&mut self, target: u64, timeout: Duration) -> HarnessResult<u64> {
+        self.wait_until_tick(target, timeout, |session| session.current_tick())
+    }
+
+    /// Highest tick reported in telemetry for `entity_id`, or `None` before
+    /// its first telemetry event.
+    pub fn entity_tick(&self, entity_id: u64) -> Option<u64> {
+        self.capture
+            .entity_ticks
+            .lock()
+            .ok()?
+            .get(&entity_id)
+            .copied()
+    }
+
+    /// Like `wait_for_tick`, but for the tick reported in `entity_id`'s own
+    /// telemetry rather than the global maximum.
+    pub fn wait_for_entity_tick(
+        &mut self,
+        entity_id: u64,
+        target: u64,
+        timeout: Duration,
+    ) -> HarnessResult<u64> {
+        self.wait_until_tick(target, timeout, |session| {
+            session.entity_tick(entity_id).unwrap_or(0)
+        })
+    }
+
+    /// Highest tick reported in telemetry for any entity in `dimension`.
+    ///
+    /// Telemetry carries no dimension, so it is attributed through the
+    /// session's cached entity dimensions; entities the session does not know
+    /// are ignored. Returns `None` before any of them sent telemetry.
+    pub fn dimension_tick(&self, dimension: u32) -> Option<u64> {
+        let ticks = self.capture.entity_ticks.lock().ok()?;
+        self.entity_dimensions
+            .iter()
+            .filter(|(_, entity_dimension)| **entity_dimension == dimension)
+            .filter_map(|(entity_id, _)| ticks.get(entity_id).copied())
+            .max()
+    }
+
+    /// Like `wait_for_tick`, but for the tick reported by entities in
+    /// `dimension`; see `dimension_tick`.
+    pub fn wait_for_dimension_tick(
+        &mut self,
+        dimension: u32,
+        target: u64,
+        timeout: Duration,
+    ) -> HarnessResult<u64> {
+        self.wait_until_tick(target, timeout, |session| {
+            session.dimension_tick(dimension).unwrap_or(0)
+        })
+    }
+
+    /// Block until every entity the session knows has reported telemetry for
+    /// `target` or later, and return the lowest per-entity tick observed.
+    ///
+    /// Use before `snapshot` to compare a world whose entities all reached
+    /// the same tick. An entity without telemetry counts as tick `0`; with no
+    /// entities this waits on the global tick like `wait_for_tick`.
+    pub fn wait_for_all_ticks(&mut self, target: u64, timeout: Duration) -> HarnessResult<u64> {
+        self.wait_until_tick(target, timeout, |session| {
+            let Ok(ticks) = session.capture.entity_ticks.lock() else {
+                return 0;
+            };
+            session
+                .entity_dimensions
+                .keys()
+                .map(|entity_id| ticks.get(entity_id).copied().unwrap_or(0))
+                .min()
+                .unwrap_or_else(|| session.current_tick())
+        })
+    }
+
+    /// Poll `observe` every `tick_wait` until it reaches `target`.
+    fn wait_until_tick(
+        &mut self,
+        target: u64,
+        timeout: Duration,
+        observe: impl Fn(&Self) -> u64,
+    ) -> HarnessResult<u64> {
+        let start = Instant::now();
+        loop {
+            self.ensure_running()?;
+            self.check_telemetry_stall()?;
+
+            let observed = observe(self);
+            if observed >= target {
+                return Ok(observed);
+            }
+            if start.elapsed() >= timeout {
+                return Err(HarnessError::TickTimeout {
+                    target,
+                    observed,
+                    timeout,
+                });
+            }
+
+            thread::sleep(self.tick_wait);
+        }
+    }
+
+    /// Advance one tick at a time until the entity's telemetry satisfies `pred`.
+    ///
+    /// Returns the first matching record. On timeout the error carries the last
+    /// record observed so failures show how close the entity got.
+    pub fn advance_until<F: FnMut(&EntityRecord) -> bool>(
+        &mut self,
+        entity_id: u64,
+        mut pred: F,
+        timeout: Duration,
+    ) -> HarnessResult<EntityRecord> {
+        let start = Instant::now();
+        let mut last = None;
+        loop {
+            self.advance_ticks(1)?;
+            if let Some(record) = self.telemetry_for(entity_id)? {
+                if pred(&record) {
+                    return Ok(record);
+                }
+                last = Some(record);
+            }
+
+            if start.elapsed() >= timeout {
+                return Err(HarnessError::WaitTimeout {
+                    what: format!("predicate on entity {entity_id} (last record: {last:?})"),
+                    timeout,
+                });
+            }
+        }
+    }
+
+    /// Wait for a server event matching `pred` and return it.
+    ///
+    /// Scanning resumes from where the previous call stopped, so an event is only
+    /// ever matched once and buffered events are considered before new arrivals.
+    pub fn wait_for_event<F: Fn(&ServerEvent) -> bool>(
+        &self,
+        pred: F,
+        timeout: Duration,
+    ) -> HarnessResult<ServerEvent> {
+        let start = Instant::now();
+        loop {
+            let mut cursor = self.event_cursor.load(Ordering::SeqCst);
+            if let Ok(events) = self.event_buffer.lock() {
+                for (position, captured) in events.since(cursor) {
+                    cursor = position + 1;
+                    if pred(&captured.event) {
+                        self.event_cursor.store(cursor, Ordering::SeqCst);
+                        return Ok(captured.event.clone());
+                    }
+                }
+            }
+            self.event_cursor.store(cursor, Ordering::SeqCst);
+
+            if start.elapsed() >= timeout {
+                return Err(HarnessError::WaitTimeout {
+                    what: "matching server event".to_string(),
+                    timeout,
+                });
+            }
+            thread::sleep(self.tick_wait);
+        }
+    }
+
+    /// Wait for a captured stdout/stderr line containing `needle`.
+    ///
+    /// Like `wait_for_event`, scanning resumes after the previous match.
+    pub fn wait_for_log(&self, needle: &str, timeout: Duration) -> HarnessResult<LogLine> {
+        self.wait_for_log_where(|line| line.contains(needle), needle, timeout)
+    }
+
+    /// Wait for a captured stdout/stderr line matching `pattern`.
+    pub fn wait_for_log_matching(
+        &self,
+        pattern: &Regex,
+        timeout: Duration,
+    ) -> HarnessResult<LogLine> {
+        self.wait_for_log_where(|line| pattern.is_match(line), pattern.as_str(), timeout)
+    }
+
+    fn wait_for_log_where(
+        &self,
+        matches: impl Fn(&str) -> bool,
+        description: &str,
+        timeout: Duration,
+    ) -> HarnessResult<LogLine> {
+        let start = Instant::now();
+        loop {
+            let mut cursor = self.log_cursor.load(Ordering::SeqCst);
+            if let Ok(buffer) = self.log_buffer.lock() {
+                for (position, line) in buffer.since(cursor) {
+                    cursor = position + 1;
+                    if matches(&line.line) {
+                        self.log_cursor.store(cursor, Ordering::SeqCst);
+                        return Ok(line.clone());
+                    }
+                }
+            }
+            self.log_cursor.store(cursor, Ordering::SeqCst);
+
+            if start.elapsed() >= timeout {
+                return Err(HarnessError::WaitTimeout {
+                    what: format!("log line matching {description:?}"),
+                    timeout,
+                });
+            }
+            thread::sleep(self.tick_wait);
+        }
+    }
+
+    /// Send a raw protocol request and return the engine's response unchanged.
+    ///
+    /// Escape hatch for requests the harness does not wrap yet. Fails with
+    /// `ConnectionClosed` once the client has been released.
+    ///
+    /// `Error` responses are also recorded in `protocol_errors`.
+    pub fn send(&self, request: ServerRequest) -> HarnessResult<ServerResponse> {
+        trace_event!(trace, ?request, "sending request");
+        let response = self.connected_client()?.send(request)?;
+        if let Some(message) = protocol_error(&response) {
+            if let Ok(mut errors) = self.protocol_errors.lock() {
+                errors.push(message);
+            }
+        }
+        Ok(response)
+    }
+
+    /// Messages of every `Error` response the engine returned to this session,
+    /// including the scenario spawns sent by `run_scenario`.
+    pub fn protocol_errors(&self) -> Vec<String> {
+        self.protocol_errors
+            .lock()
+            .map(|errors| errors.clone())
+            .unwrap_or_default()
+    }
+
+    /// Fetch the latest telemetry for an entity using an inspect request.
+    ///
+    /// Returns `None` both for ids the session does not know and for entities
+    /// the engine no longer has; use `inspect` to tell the two apart.
+    pub fn telemetry_for(&self, entity_id: u64) -> HarnessResult<Option<EntityRecord>> {
+        match self.inspect(entity_id) {
+            Ok((_, entity)) => Ok(entity),
+            Err(HarnessError::UnknownEntity(_)) => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Inspect an entity and return the engine's status alongside the record.
+    ///
+    /// Fails with `UnknownEntity` if the session has never seen the id; an
+    /// entity the engine no longer has comes back as `ResponseStatus::NotFound`.
+    pub fn inspect(&self, entity_id: u64) -> HarnessResult<(ResponseStatus, Option<EntityRecord>)> {
+        self.inspect_in(self.dimension_of(entity_id)?, entity_id)
+    }
+
+    /// Inspect several entities with the requests written in batches over a
+    /// separate connection, returning each id with its record in the order of
+    /// `ids`.
+    ///
+    /// Like `telemetry_for`, entities the engine no longer has map to `None`,
+    /// but ids the session does not know fail with `UnknownEntity` before any
+    /// request is sent. Much faster than inspecting one by one when there are
+    /// many entities. The batch does not go through the session's client, so
+    /// the engine must accept a second connection and a dropped connection is
+    /// not re-dialed.
+    pub fn inspect_many(&self, ids: &[u64]) -> HarnessResult<Vec<(u64, Option<EntityRecord>)>> {
+        let targets = ids
+            .iter()
+            .map(|&entity_id| Ok((self.dimension_of(entity_id)?, entity_id)))
+            .collect::<HarnessResult<Vec<_>>>()?;
+        let requests = targets
+            .iter()
+            .map(|&(dimension, entity_id)| ServerRequest::Inspect {
+                dimension,
+                entity_id,
+            })
+            .collect();
+        self.exchange(requests)?
+            .into_iter()
+            .zip(ids)
+            .map(|(response, &entity_id)| Ok((entity_id, inspected(response)?.1)))
+            .collect()
+    }
+
+    /// Pipeline `requests` like `send`, recording `Error` responses.
+    fn exchange(&self, requests: Vec<ServerRequest>) -> HarnessResult<Vec<ServerResponse>> {
+        let responses = pipeline::exchange(self.address, requests, self.max_frame_size, |_| false)?;
+        if let Ok(mut errors) = self.protocol_errors.lock() {
+            errors.extend(responses.iter().filter_map(protocol_error));
+        }
+        Ok(responses)
+    }
+
+    fn dimension_of(&self, entity_id: u64) -> HarnessResult<u32> {
+        self.entity_dimensions
+            .get(&entity_id)
+            .copied()
+            .ok_or(HarnessError::UnknownEntity(entity_id))
+    }
+
+    fn inspect_in(
+        &self,
+        dimension: u32,
+        entity_id: u64,
+    ) -> HarnessResult<(ResponseStatus, Option<EntityRecord>)> {
+        inspected(self.send(ServerRequest::Inspect {
+            dimension,
+            entity_id,
+        })?)
+    }
+
+    /// Inspect every entity the engine currently lists, sorted by entity id.
+    ///
+    /// The inspect requests are pipelined like `inspect_many`, so the engine
+    /// must accept a second connection. Entities that disappear between the
+    /// list and inspect requests are skipped.
+    pub fn snapshot(&self) -> HarnessResult<Vec<EntityRecord>> {
+        let listed = listed_entities(self.send(ServerRequest::List)?)?;
+        let requests = listed
+            .iter()
+            .map(|summary| ServerRequest::Inspect {
+                dimension: summary.dimension,
+                entity_id: summary.entity_id,
+            })
+            .collect();
+        let mut records = Vec::new();
+        for response in self.exchange(requests)? {
+            let (_, entity) = inspected(response)?;
+            records.extend(entity);
+        }
+
+        records.sort_by_key(|record| record.entity_id);
+        Ok(records)
+    }
+
+    /// Snapshot every entity and write one CSV row per record, tagged with the current tick.
+    ///
+    /// Missing position, velocity or mass values are written as empty cells.
+    pub fn write_telemetry_csv(&self, path: &Path) -> HarnessResult<()> {
+        let records = self.snapshot()?;
+        let tick = self.max_tick.load(Ordering::SeqCst);
+
+        let mut out = BufWriter::new(File::create(path)?);
+        writeln!(
+            out,
+            "tick,entity_id,kind,dimension,position_x,position_y,velocity_x,velocity_y,mass"
+        )?;
+        for record in records {
+            let (position_x, position_y) = csv_pair(record.position);
+            let (velocity_x, velocity_y) = csv_pair(record.velocity);
+            let mass = record.mass.map(|mass| mass.to_string()).unwrap_or_default();
+            writeln!(
+                out,
+                "{tick},{},{},{},{position_x},{position_y},{velocity_x},{velocity_y},{mass}",
+                record.entity_id,
+                csv_field(&record.kind),
+                record.dimension,
+            )?;
+        }
+        out.flush()?;
+        Ok(())
+    }
+
+    /// Return a copy of every buffered server event.
+    pub fn events(&self) -> Vec<ServerEvent> {
+        self.event_buffer
+            .lock()
+            .map(|events| {
+                events
+                    .iter()
+                    .map(|captured| captured.event.clone())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Count the buffered events by variant and telemetry by ship.
+    ///
+    /// Like `events`, only reflects what `EngineConfig::with_event_capacity` retained.
+    pub fn event_stats(&self) -> EventStats {
+        let mut stats = EventStats::default();
+        if let Ok(events) = self.event_buffer.lock() {
+            for captured in events.iter() {
+                match &captured.event {
+                    ServerEvent::Telemetry { ship, .. } => {
+                        stats.telemetry_events += 1;
+                        stats.ships.insert(ship.clone());
+                        *stats.telemetry_per_ship.entry(ship.clone()).or_default() += 1;
+                    }
+                    ServerEvent::Log { .. } => stats.log_events += 1,
+                }
+            }
+        }
+        stats
+    }
+
+    /// Return buffered events for an entity: telemetry by id and logs mentioning the id.
+    pub fn events_for(&self, entity_id: u64) -> Vec<ServerEvent> {
+        let id_text = entity_id.to_string();
+        self.events()
+            .into_iter()
+            .filter(|event| match event {
+                ServerEvent::Telemetry { id, .. } => *id == entity_id,
+                ServerEvent::Log { message } => message.contains(&id_text),
+            })
+            .collect()
+    }
+
+    /// Parse the `message` of every buffered telemetry event for an entity,
+    /// in capture order.
+    pub fn telemetry_fields_for(&self, entity_id: u64) -> Vec<TelemetryFields> {
+        self.events()
+            .iter()
+            .filter_map(|event| match event {
+                ServerEvent::Telemetry { id, message, .. } if *id == entity_id => {
+                    Some(TelemetryFields::parse(message))
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Receive future server events that match `filter` on a dedicated channel.
+    ///
+    /// Each call registers an independent subscriber fed by the event collector;
+    /// it is removed once its receiver is dropped.
+    pub fn subscribe_events<F: Fn(&ServerEvent) -> bool + Send + 'static>(
+        &self,
+        filter: F,
+    ) -> mpsc::Receiver<ServerEvent> {
+        let (tx, rx) = mpsc::channel();
+        if let Ok(mut subscribers) = self.event_subscribers.lock() {
+            subscribers.push(EventSubscriber {
+                filter: Box::new(filter),
+                tx,
+            });
+        }
+        rx
+    }
+
+    /// Return all captured log lines for an entity id (matching telemetry events and stdout).
+    pub fn logs_for(&self, entity_id: u64) -> Vec<LogLine> {
         let mut lines = Vec::new();
         let id_text = entity_id.to_string();
 
@@ -324,27 +1923,24 @@ impl Session {
         }
 
         if let Ok(events) = self.event_buffer.lock() {
-            for event in events.iter() {
-                match event {
+            for captured in events.iter() {
+                let line = match &captured.event {
                     ServerEvent::Telemetry {
                         id,
                         tick,
                         ship,
                         message,
-                    } if *id == entity_id => {
-                        lines.push(LogLine {
-                            stream: LogStream::Event,
-                            line: format!("tick {tick} [{ship}]: {message}"),
-                        });
-                    }
-                    ServerEvent::Log { message } if message.contains(&id_text) => {
-                        lines.push(LogLine {
-                            stream: LogStream::Event,
-                            line: message.clone(),
-                        });
-                    }
-                    _ => {}
-                }
+                    } if *id == entity_id => format!("tick {tick} [{ship}]: {message}"),
+                    ServerEvent::Log { message } if message.contains(&id_text) => message.clone(),
+                    _ => continue,
+                };
+                lines.push(LogLine {
+                    stream: LogStream::Event,
+                    level: self.capture.level_parser.parse(&line),
+                    line,
+                    captured_at: captured.captured_at,
+                    seq: captured.seq,
+                });
             }
         }
 
@@ -358,81 +1954,791 @@ impl Session {
             lines.extend(buffer.iter().cloned());
         }
         if let Ok(events) = self.event_buffer.lock() {
-            lines.extend(events.iter().filter_map(|event| match event {
-                ServerEvent::Telemetry {
-                    id,
-                    tick,
-                    ship,
-                    message,
-                } => Some(LogLine {
-                    stream: LogStream::Event,
-                    line: format!("entity {id} tick {tick} [{ship}]: {message}"),
-                }),
-                ServerEvent::Log { message } => Some(LogLine {
-                    stream: LogStream::Event,
-                    line: message.clone(),
-                }),
-            }));
+            let levels = &self.capture.level_parser;
+            lines.extend(
+                events
+                    .iter()
+                    .map(|captured| event_log_line(captured, levels)),
+            );
+        }
+        lines
+    }
+
+    /// `all_logs` as one newline-separated string of `[STREAM] line` entries,
+    /// for `contains`-style assertions.
+    pub fn logs_as_string(&self) -> String {
+        join_lines(&self.all_logs())
+    }
+
+    /// `logs_for` as one newline-separated string of `[STREAM] line` entries.
+    pub fn logs_for_as_string(&self, entity_id: u64) -> String {
+        join_lines(&self.logs_for(entity_id))
+    }
+
+    /// Return stdout, stderr and event-derived lines merged in capture order.
+    ///
+    /// Lines are sorted by capture time, then by sequence number. Ordering is
+    /// best-effort: the streams are read by separate threads, so lines written
+    /// close together by the engine may be captured in a different order.
+    pub fn timeline(&self) -> Vec<LogLine> {
+        let mut lines = self.all_logs();
+        lines.sort_by_key(|line| (line.captured_at, line.seq));
+        lines
+    }
+
+    /// Cursor positioned after the last stdout/stderr line captured so far.
+    pub fn log_cursor(&self) -> LogCursor {
+        LogCursor(
+            self.log_buffer
+                .lock()
+                .map(|buffer| buffer.end())
+                .unwrap_or_default(),
+        )
+    }
+
+    /// Return stdout/stderr lines captured since `cursor` and advance it past them.
+    ///
+    /// Lines evicted by `EngineConfig::with_log_capacity` before they were read
+    /// are skipped.
+    pub fn logs_since(&self, cursor: &mut LogCursor) -> Vec<LogLine> {
+        let Ok(buffer) = self.log_buffer.lock() else {
+            return Vec::new();
+        };
+        let lines = buffer
+            .since(cursor.0)
+            .map(|(_, line)| line.clone())
+            .collect();
+        cursor.0 = cursor.0.max(buffer.end());
+        lines
+    }
+
+    /// Fail if any captured line contains one of `patterns`.
+    ///
+    /// An empty `patterns` slice checks for `ERROR` and `panic`, and also
+    /// flags every line detected at `LogLevel::Error`. The error lists every
+    /// offending line.
+    pub fn assert_no_errors(&self, patterns: &[&str]) -> HarnessResult<()> {
+        let by_level = patterns.is_empty();
+        let patterns = if by_level {
+            DEFAULT_ERROR_PATTERNS
+        } else {
+            patterns
+        };
+        let offending: Vec<String> = self
+            .all_logs()
+            .iter()
+            .filter(|line| {
+                (by_level && line.level() == Some(LogLevel::Error))
+                    || patterns.iter().any(|pattern| line.line.contains(pattern))
+            })
+            .map(LogLine::to_string)
+            .collect();
+
+        if offending.is_empty() {
+            Ok(())
+        } else {
+            Err(HarnessError::ErrorsLogged(offending))
+        }
+    }
+
+    /// Find lines containing `needle` along with up to `context` lines on either side.
+    ///
+    /// Works on the merged `timeline`, like `grep -C`: groups whose context
+    /// overlaps or touches are merged into one.
+    pub fn grep(&self, needle: &str, context: usize) -> Vec<Vec<LogLine>> {
+        let timeline = self.timeline();
+        let matches: Vec<usize> = timeline
+            .iter()
+            .enumerate()
+            .filter(|(_, line)| line.line.contains(needle))
+            .map(|(index, _)| index)
+            .collect();
+
+        context_ranges(&matches, context, timeline.len())
+            .into_iter()
+            .map(|range| timeline[range].to_vec())
+            .collect()
+    }
+
+    /// Return captured lines from a single stream, in insertion order.
+    pub fn logs_by_stream(&self, stream: LogStream) -> Vec<LogLine> {
+        self.all_logs()
+            .into_iter()
+            .filter(|line| line.stream == stream)
+            .collect()
+    }
+
+    /// Return captured lines whose detected level is exactly `level`, across
+    /// streams, like `all_logs`.
+    ///
+    /// Lines without a recognizable level are never included; filter
+    /// `all_logs` on `LogLine::level` for thresholds such as "warn or worse".
+    pub fn logs_at_level(&self, level: LogLevel) -> Vec<LogLine> {
+        self.all_logs()
+            .into_iter()
+            .filter(|line| line.level() == Some(level))
+            .collect()
+    }
+
+    /// Parse every captured line that is a JSON object, in `all_logs` order.
+    ///
+    /// Lines that are not JSON objects are skipped; see `LogLine::as_json`.
+    pub fn json_logs(&self) -> Vec<serde_json::Value> {
+        self.all_logs()
+            .iter()
+            .filter_map(LogLine::as_json)
+            .collect()
+    }
+
+    /// Return captured stdout lines.
+    pub fn stdout_logs(&self) -> Vec<LogLine> {
+        self.logs_by_stream(LogStream::Stdout)
+    }
+
+    /// Return captured stderr lines.
+    pub fn stderr_logs(&self) -> Vec<LogLine> {
+        self.logs_by_stream(LogStream::Stderr)
+    }
+
+    /// Return the last `n` captured stderr lines.
+    pub fn last_stderr(&self, n: usize) -> Vec<LogLine> {
+        let mut lines = self.stderr_logs();
+        lines.drain(..lines.len().saturating_sub(n));
+        lines
+    }
+
+    /// Return lines derived from server events.
+    pub fn event_logs(&self) -> Vec<LogLine> {
+        self.logs_by_stream(LogStream::Event)
+    }
+
+    /// Return the `(tick, world_hash)` pairs reported in captured logs, sorted by tick.
+    ///
+    /// When the same tick is reported more than once the first hash seen is kept.
+    pub fn world_hashes(&self) -> Vec<(u64, String)> {
+        let mut hashes = BTreeMap::new();
+        for line in self.all_logs() {
+            if let Some((tick, hash)) = world_hash::parse_hash_line(&line.line) {
+                hashes.entry(tick).or_insert(hash);
+            }
+        }
+        hashes.into_iter().collect()
+    }
+
+    /// Return the world hash reported for a specific tick, if any.
+    pub fn world_hash_at(&self, tick: u64) -> Option<String> {
+        self.all_logs()
+            .iter()
+            .filter_map(|line| world_hash::parse_hash_line(&line.line))
+            .find(|(hash_tick, _)| *hash_tick == tick)
+            .map(|(_, hash)| hash)
+    }
+
+    /// Return the phase order logged for a dimension at each tick, sorted by tick.
+    ///
+    /// As with `world_hashes`, the first line reporting a tick wins so traces
+    /// echoed on several streams are not double-counted.
+    pub fn phase_traces(&self, dimension: u32) -> Vec<(u64, Vec<String>)> {
+        let mut traces: BTreeMap<u64, Vec<String>> = BTreeMap::new();
+        for line in self.all_logs() {
+            let mut line_traces: BTreeMap<u64, Vec<String>> = BTreeMap::new();
+            for (entry_dimension, tick, phase) in phase_trace::parse_phase_line(&line.line) {
+                if entry_dimension == dimension {
+                    line_traces.entry(tick).or_default().push(phase);
+                }
+            }
+            for (tick, phases) in line_traces {
+                traces.entry(tick).or_insert(phases);
+            }
+        }
+        traces.into_iter().collect()
+    }
+
+    /// Return the phase order logged for a dimension at a specific tick.
+    pub fn phase_order_at(&self, dimension: u32, tick: u64) -> Option<Vec<String>> {
+        self.phase_traces(dimension)
+            .into_iter()
+            .find(|(trace_tick, _)| *trace_tick == tick)
+            .map(|(_, phases)| phases)
+    }
+
+    /// Take ownership of every captured line, leaving the buffers empty.
+    ///
+    /// Returns stdout/stderr lines followed by event-derived lines, like `all_logs`,
+    /// so repeated calls only yield lines captured since the previous call.
+    pub fn take_logs(&self) -> Vec<LogLine> {
+        let mut lines = Vec::new();
+        if let Ok(mut buffer) = self.log_buffer.lock() {
+            lines.extend(buffer.drain());
+        }
+        if let Ok(mut events) = self.event_buffer.lock() {
+            let levels = &self.capture.level_parser;
+            lines.extend(
+                events
+                    .drain()
+                    .iter()
+                    .map(|captured| event_log_line(captured, levels)),
+            );
         }
         lines
     }
 
+    /// Number of log lines evicted because `EngineConfig::with_log_capacity` was reached.
+    pub fn dropped_log_count(&self) -> u64 {
+        self.log_buffer
+            .lock()
+            .map(|buffer| buffer.dropped())
+            .unwrap_or_default()
+    }
+
+    /// Number of telemetry events dropped by `EngineConfig::dedupe_telemetry`;
+    /// always `0` without it.
+    pub fn duplicate_telemetry_count(&self) -> u64 {
+        self.capture.duplicate_telemetry.load(Ordering::SeqCst)
+    }
+
+    /// Number of server events evicted because `EngineConfig::with_event_capacity` was reached.
+    pub fn dropped_event_count(&self) -> u64 {
+        self.event_buffer
+            .lock()
+            .map(|buffer| buffer.dropped())
+            .unwrap_or_default()
+    }
+
+    /// Write every captured log line to `path`, one per line, prefixed with its stream.
+    pub fn write_logs(&self, path: &Path) -> HarnessResult<()> {
+        let mut out = BufWriter::new(File::create(path)?);
+        for line in self.all_logs() {
+            writeln!(out, "{line}")?;
+        }
+        out.flush()?;
+        Ok(())
+    }
+
+    /// Queue bytes for the engine's stdin (requires `EngineConfig::with_interactive_stdin`).
+    pub fn write_stdin(&self, bytes: &[u8]) -> HarnessResult<()> {
+        send_stdin(&self.stdin_tx, bytes)
+    }
+
+    /// Close the engine's stdin once all queued bytes are written, signalling EOF.
+    pub fn close_stdin(&mut self) {
+        self.stdin_tx.take();
+    }
+
     /// Request a graceful shutdown and wait for the engine process to exit.
-    pub fn shutdown(mut self) -> HarnessResult<()> {
+    ///
+    /// If the engine does not exit within the configured shutdown timeout it is
+    /// sent SIGTERM on Unix and, after the term grace period, killed; the
+    /// returned status reflects how it actually terminated. A remote engine is
+    /// only asked to shut down and, as there is no process to observe, `None`
+    /// is returned once it disconnects or the timeout passes.
+    pub fn shutdown(mut self) -> HarnessResult<Option<ExitStatus>> {
         self.request_shutdown()
     }
 
-    fn request_shutdown(&mut self) -> HarnessResult<()> {
-        if let Some(client) = &self.client {
+    fn request_shutdown(&mut self) -> HarnessResult<Option<ExitStatus>> {
+        self.close_stdin();
+        trace_event!(debug, "requesting engine shutdown");
+        if let Ok(Some(client)) = self.client.get_mut() {
             let _ = client.send(ServerRequest::Shutdown);
         }
+        let mut exited = self.poll_exit(self.shutdown_timeout)?;
+        if exited.is_none() && self.send_sigterm() {
+            trace_event!(
+                debug,
+                timeout = ?self.shutdown_timeout,
+                "engine ignored shutdown request, sent SIGTERM"
+            );
+            exited = self.poll_exit(self.term_grace)?;
+        }
+
+        match exited {
+            Some(status) => {
+                trace_event!(debug, ?status, "engine exited");
+                self.release_exited();
+                Ok(status)
+            }
+            // Force terminate if graceful shutdown did not complete.
+            None => self.terminate(),
+        }
+    }
+
+    /// Wait for the engine to exit on its own, without requesting a shutdown.
+    ///
+    /// Meant for scenarios that end after a fixed number of ticks. Fails with
+    /// `WaitTimeout` if the engine is still running after `timeout`, in which
+    /// case the dropped session shuts it down as usual. Returns `None` for a
+    /// remote engine, which has no process to observe, once it disconnects.
+    pub fn wait_for_exit(mut self, timeout: Duration) -> HarnessResult<Option<ExitStatus>> {
+        match self.poll_exit(timeout)? {
+            Some(status) => {
+                self.release_exited();
+                Ok(status)
+            }
+            None => Err(HarnessError::WaitTimeout {
+                what: "engine to exit".to_string(),
+                timeout,
+            }),
+        }
+    }
+
+    /// Release the connection and capture threads of an engine that has exited.
+    fn release_exited(&mut self) {
+        // `try_exit` already killed the group before reaping the engine.
+        self.drop_client();
+        self.join_workers();
+    }
+
+    /// Poll for up to `timeout` until the engine exits, returning `None` while
+    /// it still runs. A remote engine that disconnected yields `Some(None)`.
+    fn poll_exit(&mut self, timeout: Duration) -> HarnessResult<Option<Option<ExitStatus>>> {
         let start = Instant::now();
-        let timeout = Duration::from_secs(2);
         while start.elapsed() < timeout {
-            if let Some(_status) = self.child.try_wait()? {
-                self.client.take();
-                self.join_workers();
-                return Ok(());
+            let exited = if self.child.is_some() {
+                self.try_exit()?.map(Some)
+            } else {
+                self.client_disconnected().then_some(None)
+            };
+            if exited.is_some() {
+                return Ok(exited);
             }
             thread::sleep(Duration::from_millis(10));
         }
+        Ok(None)
+    }
+
+    /// Ask the engine (and its process group) to terminate with SIGTERM.
+    ///
+    /// Returns whether the signal was delivered; always `false` off Unix or
+    /// with a zero `EngineConfig::with_term_grace`.
+    fn send_sigterm(&self) -> bool {
+        if self.term_grace.is_zero() {
+            return false;
+        }
+        #[cfg(unix)]
+        if let Some(child) = &self.child {
+            if let Some(tree) = &self.process_tree {
+                return tree.signal(libc::SIGTERM);
+            }
+            if let Ok(pid) = libc::pid_t::try_from(child.id()) {
+                // SAFETY: `kill` has no memory-safety preconditions.
+                return unsafe { libc::kill(pid, libc::SIGTERM) } == 0;
+            }
+        }
+        false
+    }
 
-        // Force terminate if graceful shutdown did not complete.
-        let _ = self.child.kill();
-        let _ = self.child.wait();
-        self.client.take();
+    /// Kill the engine immediately without requesting a graceful shutdown.
+    ///
+    /// A remote engine is only disconnected from, and `None` is returned.
+    pub fn kill(mut self) -> HarnessResult<Option<ExitStatus>> {
+        self.close_stdin();
+        self.terminate()
+    }
+
+    fn terminate(&mut self) -> HarnessResult<Option<ExitStatus>> {
+        trace_event!(debug, "killing engine");
+        self.kill_group();
+        let status = match &mut self.child {
+            Some(child) => {
+                let _ = child.kill();
+                Some(child.wait())
+            }
+            None => None,
+        };
+        self.drop_client();
         self.join_workers();
-        Ok(())
+        Ok(status.transpose()?)
+    }
+
+    /// Build an `EngineExited` error carrying the stderr captured before the exit.
+    fn engine_exited(&self, status: ExitStatus) -> HarnessError {
+        // Give the readers a moment to drain what the process wrote before dying.
+        if let Some(handle) = &self.log_collector {
+            let start = Instant::now();
+            while !handle.is_finished() && start.elapsed() < EXIT_DRAIN_TIMEOUT {
+                thread::sleep(Duration::from_millis(5));
+            }
+        }
+
+        let recent_stderr = self
+            .last_stderr(EXIT_STDERR_TAIL)
+            .into_iter()
+            .map(|line| line.line)
+            .collect();
+        HarnessError::EngineExited {
+            status,
+            recent_stderr,
+        }
+    }
+
+    /// Kill every process in the engine's process group or job, if it has one.
+    ///
+    /// Only done once, and on Unix only while the engine has not been reaped,
+    /// as its pid doubles as the group id.
+    fn kill_group(&mut self) {
+        if self.group_killed {
+            return;
+        }
+        if let Some(tree) = &self.process_tree {
+            self.group_killed = true;
+            tree.kill();
+        }
+    }
+
+    fn client_disconnected(&mut self) -> bool {
+        match self.client.get_mut() {
+            Ok(Some(client)) => !client.is_connected(),
+            _ => true,
+        }
+    }
+
+    fn drop_client(&mut self) {
+        if let Ok(client) = self.client.get_mut() {
+            client.take();
+        }
     }
 
     fn join_workers(&mut self) {
-        if let Some(handle) = self.log_collector.take() {
+        if let Some(handle) = self.stdin_writer.take() {
             let _ = handle.join();
         }
-        if let Some(handle) = self.event_collector.take() {
+        if let Some(handle) = self.log_collector.take() {
             let _ = handle.join();
         }
+        if let Ok(handles) = self.event_collectors.get_mut() {
+            for handle in handles.drain(..) {
+                let _ = handle.join();
+            }
+        }
     }
 }
 
 impl Drop for Session {
     fn drop(&mut self) {
+        // A remote engine is left running; only the connection is released.
+        if self.leak_on_drop || self.child.is_none() {
+            // The engine keeps its stdout/stderr open, so only the threads that
+            // stop once the client and stdin are released can be joined here.
+            self.close_stdin();
+            self.drop_client();
+            if let Some(handle) = self.stdin_writer.take() {
+                let _ = handle.join();
+            }
+            if let Ok(handles) = self.event_collectors.get_mut() {
+                for handle in handles.drain(..) {
+                    let _ = handle.join();
+                }
+            }
+            return;
+        }
         let _ = self.request_shutdown();
     }
 }
 
+fn connect_with_retry(
+    address: SocketAddr,
+    attempts: u32,
+    delay: Duration,
+) -> HarnessResult<Client> {
+    let attempts = attempts.max(1);
+    let mut attempt = 0;
+    loop {
+        attempt += 1;
+        match Client::connect(address) {
+            Ok(client) => return Ok(client),
+            Err(source) if attempt >= attempts => {
+                return Err(HarnessError::ConnectFailed {
+                    attempts: attempt,
+                    source,
+                })
+            }
+            Err(_) => thread::sleep(delay),
+        }
+    }
+}
+
+/// Whether `child` has exited, checked without reaping it so its pid stays
+/// reserved.
+#[cfg(unix)]
+fn leader_exited(child: &Child) -> io::Result<bool> {
+    // SAFETY: an all-zero `siginfo_t` is valid, and `waitid` only writes to it.
+    let mut info: libc::siginfo_t = unsafe { std::mem::zeroed() };
+    // SAFETY: `info` outlives the call; `WNOWAIT` leaves the child waitable.
+    let result = unsafe {
+        libc::waitid(
+            libc::P_PID,
+            child.id(),
+            &mut info,
+            libc::WEXITED | libc::WNOHANG | libc::WNOWAIT,
+        )
+    };
+    if result != 0 {
+        let err = io::Error::last_os_error();
+        // Already reaped, so the group must not be signalled any more.
+        return match err.raw_os_error() {
+            Some(libc::ECHILD) => Ok(false),
+            _ => Err(err),
+        };
+    }
+    // SAFETY: `waitid` succeeded, so `info` holds the child's state.
+    Ok(unsafe { info.si_pid() } != 0)
+}
+
+#[cfg(not(unix))]
+fn leader_exited(_child: &Child) -> io::Result<bool> {
+    Ok(false)
+}
+
+/// Create a fresh directory under the system temp dir, named after this
+/// process, a per-process counter and `label` so concurrent sweeps and pools
+/// never share one.
+pub(crate) fn scratch_dir(label: &str) -> io::Result<PathBuf> {
+    static NEXT: AtomicU64 = AtomicU64::new(0);
+    loop {
+        let index = NEXT.fetch_add(1, Ordering::Relaxed);
+        let name = format!("phase-space-harness-{}-{index}-{label}", std::process::id());
+        let dir = std::env::temp_dir().join(name);
+        match std::fs::create_dir(&dir) {
+            Ok(()) => return Ok(dir),
+            // Left behind by an earlier process with the same pid.
+            Err(err) if err.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(err) => return Err(err),
+        }
+    }
+}
+
+/// Spawn, run and shut down one engine of a seed sweep.
+fn run_seed(
+    config: EngineConfig,
+    scenario: ScenarioConfig,
+    f: &mut impl FnMut(&mut Session),
+) -> HarnessResult<()> {
+    if let Some(dir) = &config.working_directory {
+        std::fs::create_dir_all(dir)?;
+    }
+    let mut session = EngineHarness::spawn(config)?.run_scenario(scenario)?;
+    f(&mut session);
+    session.shutdown()?;
+    Ok(())
+}
+
+/// Spawn one scenario entity, noting an `Error` response in `protocol_errors`.
+fn spawn_entity(
+    client: &Client,
+    spec: &SpawnSpec,
+    protocol_errors: &mut Vec<String>,
+) -> HarnessResult<EntitySummary> {
+    let request = spawn_request(spec);
+    trace_event!(trace, ?request, "sending request");
+    let response = client.send(request)?;
+    protocol_errors.extend(protocol_error(&response));
+    spawned_entity(spec, response)
+}
+
+/// Message of an `Error` response, for `Session::protocol_errors`.
+fn protocol_error(response: &ServerResponse) -> Option<String> {
+    match response {
+        ServerResponse::Error { message, .. } => Some(message.clone()),
+        _ => None,
+    }
+}
+
+fn spawn_request(spec: &SpawnSpec) -> ServerRequest {
+    ServerRequest::Spawn {
+        entity_type: spec.entity_type.clone(),
+        parameters: spec.parameters.clone(),
+        dimension: spec.dimension,
+    }
+}
+
+fn spawn_succeeded(response: &ServerResponse) -> bool {
+    matches!(
+        response,
+        ServerResponse::Spawned {
+            status: ResponseStatus::Ok,
+            ..
+        }
+    )
+}
+
+fn spawned_entity(spec: &SpawnSpec, response: ServerResponse) -> HarnessResult<EntitySummary> {
+    match response {
+        ServerResponse::Spawned { status, entity } => {
+            if status != ResponseStatus::Ok {
+                return Err(HarnessError::unexpected(format!(
+                    "spawn for {} failed with status {status:?}",
+                    spec.entity_type
+                )));
+            }
+            Ok(entity)
+        }
+        ServerResponse::Error { message, .. } => Err(HarnessError::unexpected(message)),
+        other => Err(HarnessError::unexpected(format!(
+            "spawn returned unexpected response: {other:?}"
+        ))),
+    }
+}
+
+fn list_entities(client: &Client) -> HarnessResult<Vec<EntitySummary>> {
+    trace_event!(trace, request = ?ServerRequest::List, "sending request");
+    listed_entities(client.send(ServerRequest::List)?)
+}
+
+fn inspected(response: ServerResponse) -> HarnessResult<(ResponseStatus, Option<EntityRecord>)> {
+    match response {
+        ServerResponse::InspectResult { status, entity, .. } => Ok((status, entity)),
+        other => Err(HarnessError::unexpected(format!(
+            "inspect returned unexpected response: {other:?}"
+        ))),
+    }
+}
+
+fn listed_entities(response: ServerResponse) -> HarnessResult<Vec<EntitySummary>> {
+    match response {
+        ServerResponse::Listed { status, entities } => {
+            if status != ResponseStatus::Ok {
+                return Err(HarnessError::unexpected(format!(
+                    "list failed with status {status:?}"
+                )));
+            }
+            Ok(entities)
+        }
+        other => Err(HarnessError::unexpected(format!(
+            "list returned unexpected response: {other:?}"
+        ))),
+    }
+}
+
+/// Expand sorted match indices by `context` lines, merging ranges that overlap or touch.
+fn context_ranges(matches: &[usize], context: usize, len: usize) -> Vec<Range<usize>> {
+    let mut ranges: Vec<Range<usize>> = Vec::new();
+    for &index in matches {
+        let start = index.saturating_sub(context);
+        let end = index.saturating_add(context).saturating_add(1).min(len);
+        match ranges.last_mut() {
+            Some(last) if start <= last.end => last.end = last.end.max(end),
+            _ => ranges.push(start..end),
+        }
+    }
+    ranges
+}
+
+fn csv_pair(pair: Option<(f64, f64)>) -> (String, String) {
+    pair.map(|(x, y)| (x.to_string(), y.to_string()))
+        .unwrap_or_default()
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn send_stdin(tx: &Option<mpsc::Sender<Vec<u8>>>, bytes: &[u8]) -> HarnessResult<()> {
+    let tx = tx
+        .as_ref()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotConnected, "engine stdin is not open"))?;
+    tx.send(bytes.to_vec()).map_err(|_| {
+        io::Error::new(io::ErrorKind::BrokenPipe, "engine stdin writer stopped").into()
+    })
+}
+
+fn spawn_stdin_writer(
+    mut stdin: ChildStdin,
+    rx: mpsc::Receiver<Vec<u8>>,
+) -> thread::JoinHandle<()> {
+    thread::spawn(move || {
+        while let Ok(bytes) = rx.recv() {
+            if stdin
+                .write_all(&bytes)
+                .and_then(|()| stdin.flush())
+                .is_err()
+            {
+                break;
+            }
+        }
+    })
+}
+
+fn event_log_line(captured: &CapturedEvent, levels: &LevelParser) -> LogLine {
+    let line = match &captured.event {
+        ServerEvent::Telemetry {
+            id,
+            tick,
+            ship,
+            message,
+        } => format!("entity {id} tick {tick} [{ship}]: {message}"),
+        ServerEvent::Log { message } => message.clone(),
+    };
+    LogLine {
+        stream: LogStream::Event,
+        level: levels.parse(&line),
+        line,
+        captured_at: captured.captured_at,
+        seq: captured.seq,
+    }
+}
+
+fn tee_line(sink: &Option<LogSink>, line: &LogLine) {
+    if let Some(sink) = sink {
+        if let Ok(mut file) = sink.lock() {
+            let _ = writeln!(file, "{line}");
+        }
+    }
+}
+
+fn join_lines(lines: &[LogLine]) -> String {
+    lines
+        .iter()
+        .map(LogLine::to_string)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Whether `PHASE_SPACE_ECHO_LOGS` asks for captured output to be echoed.
+fn echo_logs_from_env() -> bool {
+    std::env::var_os("PHASE_SPACE_ECHO_LOGS").is_some_and(|value| value != "0")
+}
+
 fn spawn_log_reader<R: std::io::Read + Send + 'static>(
     reader: R,
     stream: LogStream,
     tx: mpsc::Sender<LogLine>,
+    sink: Option<LogSink>,
+    trim: bool,
+    capture: Arc<CaptureState>,
 ) {
     thread::spawn(move || {
+        let _guard = PanicGuard {
+            capture: capture.clone(),
+            name: "log reader",
+        };
+        // Split raw bytes so lines with invalid UTF-8 are kept (lossily) rather
+        // than dropped.
         let buf_reader = BufReader::new(reader);
-        for line in buf_reader.lines().flatten() {
-            let _ = tx.send(LogLine {
+        for bytes in buf_reader.split(b'\n').map_while(Result::ok) {
+            let text = String::from_utf8_lossy(&bytes);
+            let text = if trim {
+                text.trim()
+            } else {
+                text.strip_suffix('\r').unwrap_or(&text)
+            };
+            let (captured_at, seq) = capture.stamp();
+            let line = LogLine {
                 stream,
-                line: line.trim().to_string(),
-            });
+                level: capture.level_parser.parse(text),
+                line: text.to_string(),
+                captured_at,
+                seq,
+            };
+            tee_line(&sink, &line);
+            if capture.echo_logs {
+                eprintln!("{line}");
+            }
+            let _ = tx.send(line);
         }
     });
 }
@@ -440,13 +2746,15 @@ fn spawn_log_reader<R: std::io::Read + Send + 'static>(
 fn wait_for_listen_address(
     child: &mut Child,
     log_rx: &mpsc::Receiver<LogLine>,
-    log_buffer: &Arc<Mutex<Vec<LogLine>>>,
+    log_buffer: &Arc<Mutex<CaptureBuffer<LogLine>>>,
+    pattern: &str,
     timeout: Duration,
-) -> HarnessResult<SocketAddr> {
+) -> HarnessResult<(SocketAddr, Option<u64>)> {
     let start = Instant::now();
+    let mut seed = None;
     while start.elapsed() < timeout {
         if let Some(status) = child.try_wait()? {
-            return Err(HarnessError::EngineExited(status));
+            return Err(startup_exited(status, log_rx, log_buffer));
         }
 
         match log_rx.recv_timeout(Duration::from_millis(50)) {
@@ -454,8 +2762,9 @@ fn wait_for_listen_address(
                 if let Ok(mut buffer) = log_buffer.lock() {
                     buffer.push(line.clone());
                 }
-                if let Some(addr) = parse_listen_line(&line.line) {
-                    return Ok(addr);
+                seed = seed.or_else(|| parse_seed_line(&line.line));
+                if let Some(addr) = parse_listen_line(&line.line, pattern) {
+                    return Ok((addr, seed));
                 }
             }
             Err(mpsc::RecvTimeoutError::Timeout) => continue,
@@ -463,22 +2772,156 @@ fn wait_for_listen_address(
         }
     }
 
-    Err(HarnessError::StartupTimeout(timeout))
+    Err(startup_timeout(timeout, log_rx, log_buffer))
+}
+
+/// Poll until the engine accepts TCP connections on `address`.
+fn wait_for_port(
+    child: &mut Child,
+    address: SocketAddr,
+    log_rx: &mpsc::Receiver<LogLine>,
+    log_buffer: &Arc<Mutex<CaptureBuffer<LogLine>>>,
+    timeout: Duration,
+) -> HarnessResult<()> {
+    let start = Instant::now();
+    while start.elapsed() < timeout {
+        if let Some(status) = child.try_wait()? {
+            return Err(startup_exited(status, log_rx, log_buffer));
+        }
+        if TcpStream::connect_timeout(&address, Duration::from_millis(50)).is_ok() {
+            return Ok(());
+        }
+        thread::sleep(Duration::from_millis(20));
+    }
+
+    Err(startup_timeout(timeout, log_rx, log_buffer))
+}
+
+/// Markers checked by `Session::assert_no_errors` when none are given.
+const DEFAULT_ERROR_PATTERNS: &[&str] = &["ERROR", "panic"];
+
+/// Number of stderr lines attached to an `EngineExited` error.
+const EXIT_STDERR_TAIL: usize = 10;
+
+/// How long to wait for output of an exited engine to be collected.
+const EXIT_DRAIN_TIMEOUT: Duration = Duration::from_millis(200);
+
+/// Build an `EngineExited` error for an engine that died before it was ready.
+fn startup_exited(
+    status: ExitStatus,
+    log_rx: &mpsc::Receiver<LogLine>,
+    log_buffer: &Arc<Mutex<CaptureBuffer<LogLine>>>,
+) -> HarnessError {
+    let deadline = Instant::now() + EXIT_DRAIN_TIMEOUT;
+    let mut pending = Vec::new();
+    while let Some(remaining) = deadline.checked_duration_since(Instant::now()) {
+        match log_rx.recv_timeout(remaining) {
+            Ok(line) => pending.push(line),
+            Err(_) => break,
+        }
+    }
+
+    let mut recent_stderr = Vec::new();
+    if let Ok(mut buffer) = log_buffer.lock() {
+        for line in pending {
+            buffer.push(line);
+        }
+        recent_stderr = buffer
+            .iter()
+            .filter(|line| line.stream == LogStream::Stderr)
+            .map(|line| line.line.clone())
+            .collect();
+    }
+    recent_stderr.drain(..recent_stderr.len().saturating_sub(EXIT_STDERR_TAIL));
+
+    HarnessError::EngineExited {
+        status,
+        recent_stderr,
+    }
+}
+
+/// Number of captured lines attached to a `StartupTimeout` error.
+const STARTUP_LOG_TAIL: usize = 20;
+
+/// Build a `StartupTimeout` error carrying the tail of everything captured so far.
+fn startup_timeout(
+    timeout: Duration,
+    log_rx: &mpsc::Receiver<LogLine>,
+    log_buffer: &Arc<Mutex<CaptureBuffer<LogLine>>>,
+) -> HarnessError {
+    let recent_logs = match log_buffer.lock() {
+        Ok(mut buffer) => {
+            for line in log_rx.try_iter() {
+                buffer.push(line);
+            }
+            let lines: Vec<String> = buffer.iter().map(LogLine::to_string).collect();
+            lines[lines.len().saturating_sub(STARTUP_LOG_TAIL)..].to_vec()
+        }
+        Err(_) => Vec::new(),
+    };
+
+    HarnessError::StartupTimeout {
+        timeout,
+        recent_logs,
+    }
 }
 
-fn parse_listen_line(line: &str) -> Option<SocketAddr> {
-    let needle = "listening on";
+/// Find `pattern` (case-insensitively) and parse the first address that follows it.
+fn parse_listen_line(line: &str, pattern: &str) -> Option<SocketAddr> {
+    let needle = pattern.to_ascii_lowercase();
     let lower = line.to_ascii_lowercase();
-    let idx = lower.find(needle)?;
-    let after = line[idx + needle.len()..].trim();
-    after.parse().ok()
+    let idx = lower.find(&needle)?;
+    line[idx + needle.len()..]
+        .split_whitespace()
+        .find_map(parse_addr_token)
+}
+
+/// Average velocity between two `(tick, position)` samples `dt` seconds per tick apart.
+fn finite_difference(
+    (from_tick, from): (u64, (f64, f64)),
+    (to_tick, to): (u64, (f64, f64)),
+    dt: f64,
+) -> Option<(f64, f64)> {
+    let elapsed = to_tick.checked_sub(from_tick)? as f64 * dt;
+    if elapsed <= 0.0 {
+        return None;
+    }
+    Some(((to.0 - from.0) / elapsed, (to.1 - from.1) / elapsed))
+}
+
+/// Parse the world seed from a startup line such as `world seed=1234`.
+fn parse_seed_line(line: &str) -> Option<u64> {
+    let idx = line.to_ascii_lowercase().find("seed=")?;
+    let value = &line[idx + "seed=".len()..];
+    let end = value
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(value.len());
+    value[..end].parse().ok()
+}
+
+/// Parse a socket address token, ignoring surrounding punctuation. A bare
+/// `:port` is taken to mean the IPv4 loopback address.
+fn parse_addr_token(token: &str) -> Option<SocketAddr> {
+    let token = token
+        .trim_start_matches(['(', '<', '"', '\''])
+        .trim_end_matches(['.', ',', ';', ')', '>', '"', '\'']);
+    if let Ok(addr) = token.parse() {
+        return Some(addr);
+    }
+    let port = token.strip_prefix(':')?.parse::<u16>().ok()?;
+    Some(SocketAddr::from((Ipv4Addr::LOCALHOST, port)))
 }
 
 fn spawn_log_collector(
     log_rx: mpsc::Receiver<LogLine>,
-    buffer: Arc<Mutex<Vec<LogLine>>>,
+    buffer: Arc<Mutex<CaptureBuffer<LogLine>>>,
+    capture: Arc<CaptureState>,
 ) -> thread::JoinHandle<()> {
     thread::spawn(move || {
+        let _guard = PanicGuard {
+            capture,
+            name: "log collector",
+        };
         while let Ok(line) = log_rx.recv() {
             if let Ok(mut guard) = buffer.lock() {
                 guard.push(line);
@@ -489,18 +2932,387 @@ fn spawn_log_collector(
 
 fn spawn_event_collector(
     event_rx: mpsc::Receiver<ServerEvent>,
-    buffer: Arc<Mutex<Vec<ServerEvent>>>,
+    buffer: Arc<Mutex<CaptureBuffer<CapturedEvent>>>,
+    subscribers: EventSubscribers,
     max_tick: Arc<AtomicU64>,
+    tick_window: Arc<Mutex<TickWindow>>,
+    sink: Option<LogSink>,
+    capture: Arc<CaptureState>,
 ) -> thread::JoinHandle<()> {
     thread::spawn(move || {
-        while let Ok(event) = event_rx.recv() {
-            if let Ok(mut guard) = buffer.lock() {
-                guard.push(event.clone());
+        let _guard = PanicGuard {
+            capture: capture.clone(),
+            name: "event collector",
+        };
+        let mut last_telemetry = Instant::now();
+        loop {
+            let event = match capture.stall_timeout {
+                Some(timeout) => match event_rx.recv_timeout(timeout) {
+                    Ok(event) => event,
+                    Err(mpsc::RecvTimeoutError::Timeout) => {
+                        if last_telemetry.elapsed() >= timeout {
+                            capture.telemetry_stalled.store(true, Ordering::SeqCst);
+                        }
+                        continue;
+                    }
+                    Err(mpsc::RecvTimeoutError::Disconnected) => break,
+                },
+                None => match event_rx.recv() {
+                    Ok(event) => event,
+                    Err(_) => break,
+                },
+            };
+            // Other events can keep arriving while telemetry has stopped.
+            if let Some(timeout) = capture.stall_timeout {
+                if !matches!(event, ServerEvent::Telemetry { .. })
+                    && last_telemetry.elapsed() >= timeout
+                {
+                    capture.telemetry_stalled.store(true, Ordering::SeqCst);
+                }
+            }
+            if let ServerEvent::Telemetry { id, tick, .. } = &event {
+                // Shared with collectors started by a reconnect, so telemetry the
+                // engine re-sends on a new connection is deduped too.
+                if let Ok(mut ticks) = capture.entity_ticks.lock() {
+                    match ticks.get_mut(id) {
+                        Some(latest) if capture.dedupe_telemetry && *tick <= *latest => {
+                            capture.duplicate_telemetry.fetch_add(1, Ordering::SeqCst);
+                            continue;
+                        }
+                        Some(latest) => *latest = (*latest).max(*tick),
+                        None => {
+                            ticks.insert(*id, *tick);
+                        }
+                    }
+                }
+            }
+            let (captured_at, seq) = capture.stamp();
+            let captured = CapturedEvent {
+                event,
+                captured_at,
+                seq,
+            };
+            tee_line(&sink, &event_log_line(&captured, &capture.level_parser));
+            if let Ok(mut guard) = subscribers.lock() {
+                guard.retain(|subscriber| {
+                    !(subscriber.filter)(&captured.event)
+                        || subscriber.tx.send(captured.event.clone()).is_ok()
+                });
+            }
+
+            if let ServerEvent::Telemetry { tick, .. } = captured.event {
+                last_telemetry = captured_at;
+                capture.telemetry_stalled.store(false, Ordering::SeqCst);
+                if max_tick.fetch_max(tick, Ordering::SeqCst) < tick {
+                    if let Ok(mut window) = tick_window.lock() {
+                        window.record(tick, captured_at);
+                    }
+                }
             }
 
-            if let ServerEvent::Telemetry { tick, .. } = event {
-                max_tick.fetch_max(tick, Ordering::SeqCst);
+            if let Ok(mut guard) = buffer.lock() {
+                guard.push(captured);
             }
         }
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DEFAULT_PATTERN: &str = "listening on";
+
+    #[test]
+    fn panic_guard_records_thread_panics() {
+        let capture = Arc::new(CaptureState::default());
+        let guard_capture = capture.clone();
+        let result = thread::spawn(move || {
+            let _guard = PanicGuard {
+                capture: guard_capture,
+                name: "test collector",
+            };
+            panic!("collector failure");
+        })
+        .join();
+
+        assert!(result.is_err());
+        assert_eq!(
+            capture.failure.get().map(String::as_str),
+            Some("test collector thread panicked")
+        );
+    }
+
+    #[test]
+    fn event_collector_flags_telemetry_stalls() {
+        let capture = Arc::new(CaptureState {
+            stall_timeout: Some(Duration::from_millis(20)),
+            ..CaptureState::default()
+        });
+        let (tx, rx) = mpsc::channel();
+        let collector = spawn_event_collector(
+            rx,
+            Arc::new(Mutex::new(CaptureBuffer::new(None))),
+            Arc::new(Mutex::new(Vec::new())),
+            Arc::new(AtomicU64::new(0)),
+            Arc::new(Mutex::new(TickWindow::default())),
+            None,
+            capture.clone(),
+        );
+
+        let stalled_becomes = |expected: bool| {
+            let deadline = Instant::now() + Duration::from_secs(1);
+            while capture.telemetry_stalled.load(Ordering::SeqCst) != expected {
+                if Instant::now() >= deadline {
+                    return false;
+                }
+                thread::sleep(Duration::from_millis(1));
+            }
+            true
+        };
+        assert!(stalled_becomes(true));
+
+        tx.send(ServerEvent::Telemetry {
+            id: 1,
+            tick: 1,
+            ship: "probe".to_string(),
+            message: "tick 1".to_string(),
+        })
+        .unwrap();
+        assert!(stalled_becomes(false));
+
+        drop(tx);
+        collector.join().unwrap();
+    }
+
+    #[test]
+    fn event_collector_flags_stalls_while_other_events_arrive() {
+        let timeout = Duration::from_millis(50);
+        let capture = Arc::new(CaptureState {
+            stall_timeout: Some(timeout),
+            ..CaptureState::default()
+        });
+        let (tx, rx) = mpsc::channel();
+        let collector = spawn_event_collector(
+            rx,
+            Arc::new(Mutex::new(CaptureBuffer::new(None))),
+            Arc::new(Mutex::new(Vec::new())),
+            Arc::new(AtomicU64::new(0)),
+            Arc::new(Mutex::new(TickWindow::default())),
+            None,
+            capture.clone(),
+        );
+
+        tx.send(ServerEvent::Telemetry {
+            id: 1,
+            tick: 1,
+            ship: "probe".to_string(),
+            message: "tick 1".to_string(),
+        })
+        .unwrap();
+        // Logs arrive well within the timeout, so the collector never idles.
+        let start = Instant::now();
+        while start.elapsed() < timeout * 4 {
+            tx.send(ServerEvent::Log {
+                message: "still alive".to_string(),
+            })
+            .unwrap();
+            thread::sleep(Duration::from_millis(5));
+        }
+        drop(tx);
+        collector.join().unwrap();
+
+        assert!(capture.telemetry_stalled.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn event_collector_dedupes_and_tracks_entity_ticks() {
+        let capture = Arc::new(CaptureState {
+            dedupe_telemetry: true,
+            ..CaptureState::default()
+        });
+        let buffer = Arc::new(Mutex::new(CaptureBuffer::new(None)));
+        let collect = |events: &[(u64, u64)]| {
+            let (tx, rx) = mpsc::channel();
+            let collector = spawn_event_collector(
+                rx,
+                buffer.clone(),
+                Arc::new(Mutex::new(Vec::new())),
+                Arc::new(AtomicU64::new(0)),
+                Arc::new(Mutex::new(TickWindow::default())),
+                None,
+                capture.clone(),
+            );
+            for &(id, tick) in events {
+                tx.send(ServerEvent::Telemetry {
+                    id,
+                    tick,
+                    ship: "probe".to_string(),
+                    message: format!("tick {tick}"),
+                })
+                .unwrap();
+            }
+            tx.send(ServerEvent::Log {
+                message: "not telemetry".to_string(),
+            })
+            .unwrap();
+            drop(tx);
+            collector.join().unwrap();
+        };
+
+        collect(&[(1, 1), (1, 1), (2, 1), (1, 2), (1, 1)]);
+        assert_eq!(buffer.lock().unwrap().iter().count(), 4);
+        assert_eq!(capture.duplicate_telemetry.load(Ordering::SeqCst), 2);
+
+        // A reconnect starts a new collector that still knows the captured ticks.
+        collect(&[(1, 2), (1, 3)]);
+        assert_eq!(buffer.lock().unwrap().iter().count(), 6);
+        assert_eq!(capture.duplicate_telemetry.load(Ordering::SeqCst), 3);
+        let ticks = capture.entity_ticks.lock().unwrap();
+        assert_eq!((ticks[&1], ticks[&2]), (3, 1));
+    }
+
+    #[test]
+    fn tick_window_reports_median_interval_per_tick() {
+        let start = Instant::now();
+        let mut window = TickWindow::default();
+        window.record(1, start);
+        assert_eq!(window.median_interval(1), None);
+
+        let mut at = start;
+        for (tick, gap_ms) in [(2, 10), (4, 40), (5, 12), (6, 500)] {
+            at += Duration::from_millis(gap_ms);
+            window.record(tick, at);
+        }
+        // Per-tick intervals are 10, 20, 12 and 500 ms.
+        assert_eq!(window.median_interval(1), Some(Duration::from_millis(20)));
+        assert_eq!(window.median_interval(5), None);
+    }
+
+    #[test]
+    fn context_ranges_merge_overlapping_groups() {
+        assert_eq!(context_ranges(&[5], 2, 20), vec![3..8]);
+        assert_eq!(context_ranges(&[0, 19], 1, 20), vec![0..2, 18..20]);
+        assert_eq!(context_ranges(&[4, 8], 2, 20), vec![2..11]);
+        assert_eq!(context_ranges(&[], 2, 20), Vec::<Range<usize>>::new());
+    }
+
+    #[test]
+    fn tick_window_reports_rate_over_observed_span() {
+        let start = Instant::now();
+        let mut window = TickWindow::default();
+        assert_eq!(window.rate(), None);
+
+        window.record(10, start);
+        assert_eq!(window.rate(), None);
+        window.record(60, start + Duration::from_millis(500));
+        assert_eq!(window.rate(), Some(100.0));
+    }
+
+    #[test]
+    fn parses_ipv4_listen_lines() {
+        let expected: SocketAddr = "127.0.0.1:4000".parse().unwrap();
+        assert_eq!(
+            parse_listen_line("listening on 127.0.0.1:4000", DEFAULT_PATTERN),
+            Some(expected)
+        );
+        assert_eq!(
+            parse_listen_line("[INFO] PSIP Listening on 127.0.0.1:4000.", DEFAULT_PATTERN),
+            Some(expected)
+        );
+        assert_eq!(
+            parse_listen_line("listening on: 127.0.0.1:4000 (tcp)", DEFAULT_PATTERN),
+            Some(expected)
+        );
+    }
+
+    #[test]
+    fn parses_bracketed_ipv6_listen_lines() {
+        let expected: SocketAddr = "[::1]:4000".parse().unwrap();
+        assert_eq!(
+            parse_listen_line("listening on [::1]:4000", DEFAULT_PATTERN),
+            Some(expected)
+        );
+        assert_eq!(
+            parse_listen_line("server listening on [::1]:4000, ready", DEFAULT_PATTERN),
+            Some(expected)
+        );
+    }
+
+    #[test]
+    fn parses_port_only_listen_lines() {
+        let expected: SocketAddr = "127.0.0.1:4000".parse().unwrap();
+        assert_eq!(
+            parse_listen_line("listening on :4000", DEFAULT_PATTERN),
+            Some(expected)
+        );
+        assert_eq!(
+            parse_listen_line("listening on port", DEFAULT_PATTERN),
+            None
+        );
+        assert_eq!(
+            parse_listen_line("bound to 127.0.0.1:4000", DEFAULT_PATTERN),
+            None
+        );
+    }
+
+    #[test]
+    fn honors_custom_listen_pattern() {
+        let expected: SocketAddr = "127.0.0.1:5000".parse().unwrap();
+        assert_eq!(
+            parse_listen_line("PSIP server bound to 127.0.0.1:5000", "server bound to"),
+            Some(expected)
+        );
+    }
+
+    #[test]
+    fn finite_difference_scales_by_ticks_and_dt() {
+        assert_eq!(
+            finite_difference((2, (0.0, 1.0)), (6, (2.0, 0.0)), 0.5),
+            Some((1.0, -0.5))
+        );
+        assert_eq!(
+            finite_difference((6, (0.0, 0.0)), (6, (1.0, 0.0)), 0.5),
+            None
+        );
+        assert_eq!(
+            finite_difference((6, (0.0, 0.0)), (2, (1.0, 0.0)), 0.5),
+            None
+        );
+    }
+
+    #[test]
+    fn parses_reported_world_seed() {
+        assert_eq!(parse_seed_line("engine starting, seed=1234"), Some(1234));
+        assert_eq!(parse_seed_line("World Seed=42 (clamped)"), Some(42));
+        assert_eq!(parse_seed_line("seed=abc"), None);
+        assert_eq!(parse_seed_line("listening on 127.0.0.1:5000"), None);
+    }
+
+    #[test]
+    fn scratch_dirs_are_unique_per_call() {
+        let first = scratch_dir("seed-7").expect("first dir");
+        let second = scratch_dir("seed-7").expect("second dir");
+        assert_ne!(first, second);
+        assert!(first.is_dir() && second.is_dir());
+        let _ = std::fs::remove_dir_all(&first);
+        let _ = std::fs::remove_dir_all(&second);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn leader_exit_is_seen_without_reaping() {
+        let mut child = Command::new("true").spawn().expect("`true` should run");
+        let start = Instant::now();
+        while !leader_exited(&child).expect("waitid") {
+            assert!(
+                start.elapsed() < Duration::from_secs(5),
+                "child never exited"
+            );
+            thread::sleep(Duration::from_millis(5));
+        }
+        let status = child.try_wait().expect("try_wait");
+        assert!(status.is_some_and(|status| status.success()));
+        assert!(!leader_exited(&child).expect("waitid after reaping"));
+    }
+}
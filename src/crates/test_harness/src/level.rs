@@ -0,0 +1,83 @@
+//! Severity detection for captured log lines.
+
+use std::fmt;
+use std::sync::Arc;
+
+/// Severity of a captured log line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum LogLevel {
+    Trace,
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+impl LogLevel {
+    /// Parse a level name such as `WARN`, `warning` or `Err` (case-insensitive).
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "trace" => Some(LogLevel::Trace),
+            "debug" => Some(LogLevel::Debug),
+            "info" => Some(LogLevel::Info),
+            "warn" | "warning" => Some(LogLevel::Warn),
+            "error" | "err" | "fatal" => Some(LogLevel::Error),
+            _ => None,
+        }
+    }
+}
+
+type LevelFn = dyn Fn(&str) -> Option<LogLevel> + Send + Sync;
+
+/// Level detection applied to every captured line; see
+/// `EngineConfig::with_level_parser`.
+#[derive(Clone)]
+pub(crate) struct LevelParser(Arc<LevelFn>);
+
+impl LevelParser {
+    pub(crate) fn new(parser: impl Fn(&str) -> Option<LogLevel> + Send + Sync + 'static) -> Self {
+        Self(Arc::new(parser))
+    }
+
+    pub(crate) fn parse(&self, line: &str) -> Option<LogLevel> {
+        (self.0)(line)
+    }
+}
+
+impl Default for LevelParser {
+    fn default() -> Self {
+        Self::new(detect_level)
+    }
+}
+
+impl fmt::Debug for LevelParser {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("LevelParser")
+    }
+}
+
+/// Default heuristic: a leading `[LEVEL]` or `LEVEL:` token.
+pub(crate) fn detect_level(line: &str) -> Option<LogLevel> {
+    let line = line.trim_start();
+    let token = match line.strip_prefix('[') {
+        Some(rest) => rest.split_once(']')?.0,
+        None => line.split_once(':')?.0,
+    };
+    LogLevel::from_name(token.trim())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_leading_level_tokens() {
+        assert_eq!(detect_level("[WARN] plugin slow"), Some(LogLevel::Warn));
+        assert_eq!(detect_level("  error: tick overrun"), Some(LogLevel::Error));
+        assert_eq!(detect_level("[Info] ready"), Some(LogLevel::Info));
+        assert_eq!(detect_level("Warning: low fuel"), Some(LogLevel::Warn));
+        assert_eq!(detect_level("listening on 127.0.0.1:5000"), None);
+        assert_eq!(detect_level("[plugin] loaded"), None);
+        assert_eq!(detect_level("tick 3 ERROR: late"), None);
+    }
+}
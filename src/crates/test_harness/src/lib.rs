@@ -25,11 +25,48 @@
 //!     println!("entity at {:?}", state.position);
 //! }
 //! ```
+//!
+//! With the `tracing` feature the harness emits `tracing` events for engine
+//! launch, connection, each request sent and the shutdown path taken.
+
+/// Emit a `tracing` event with the `tracing` feature; expands to nothing
+/// without it.
+macro_rules! trace_event {
+    ($level:ident, $($arg:tt)+) => {
+        #[cfg(feature = "tracing")]
+        tracing::$level!($($arg)+);
+    };
+}
 
+#[cfg(feature = "async")]
+mod async_session;
+mod buffer;
 mod config;
+mod diff;
 mod error;
 mod harness;
+mod level;
+mod locate;
+pub mod phase_trace;
+mod pipeline;
+mod pool;
+mod process_tree;
+pub mod scenario;
+mod telemetry;
+#[cfg(feature = "test-support")]
+pub mod testing;
+mod usage;
+mod wire;
+pub mod world_hash;
 
+#[cfg(feature = "async")]
+pub use async_session::AsyncSession;
 pub use config::{EngineConfig, ScenarioConfig, SpawnSpec};
+pub use diff::{record_diff, EntityDelta, EntityDiff};
 pub use error::{HarnessError, HarnessResult};
-pub use harness::{EngineHarness, LogLine, LogStream, Session};
+pub use harness::{EngineHarness, EventStats, LogCursor, LogLine, LogStream, PreviousRun, Session};
+pub use level::LogLevel;
+pub use locate::{locate_context_plugin, locate_engine_binary};
+pub use pool::HarnessPool;
+pub use telemetry::TelemetryFields;
+pub use usage::ResourceUsage;
@@ -0,0 +1,65 @@
+use std::env::consts::{DLL_PREFIX, DLL_SUFFIX};
+use std::path::PathBuf;
+
+/// Find the engine binary for integration tests.
+///
+/// `CARGO_BIN_EXE_phase-space-engine` (or `CARGO_BIN_EXE_phase_space_engine`)
+/// wins when set; otherwise the binary is looked up in the target directory
+/// next to the running test executable (with an `.exe` suffix on Windows).
+/// Returns `None` if neither exists.
+pub fn locate_engine_binary() -> Option<PathBuf> {
+    locate_binary("phase-space-engine")
+}
+
+fn locate_binary(name: &str) -> Option<PathBuf> {
+    let env_vars = [
+        format!("CARGO_BIN_EXE_{name}"),
+        format!("CARGO_BIN_EXE_{}", name.replace('-', "_")),
+    ];
+    if let Some(path) = env_vars.iter().find_map(std::env::var_os) {
+        return Some(PathBuf::from(path));
+    }
+
+    // Test executables live in target/<profile>/deps; binaries one level up.
+    let mut path = std::env::current_exe().ok()?;
+    path.pop(); // deps
+    path.pop(); // debug or release
+    path.push(name);
+    if cfg!(windows) {
+        path.set_extension("exe");
+    }
+    path.exists().then_some(path)
+}
+
+/// Find a context plugin built from the crate `crate_name` (e.g. `arls_plugin`).
+///
+/// `PHASE_SPACE_PLUGIN_<CRATE_NAME>` (upper-cased, `-` replaced by `_`) wins
+/// when set. Otherwise the platform library name (`lib<name>.so`,
+/// `<name>.dll`, ...) is searched for next to the running executable, then in
+/// the `debug` and `release` target directories and their `deps`
+/// subdirectories. Returns `None` if the plugin has not been built.
+pub fn locate_context_plugin(crate_name: &str) -> Option<PathBuf> {
+    let lib_name = crate_name.replace('-', "_");
+    let override_var = format!("PHASE_SPACE_PLUGIN_{}", lib_name.to_uppercase());
+    if let Some(path) = std::env::var_os(override_var) {
+        return Some(PathBuf::from(path));
+    }
+
+    let file_name = format!("{DLL_PREFIX}{lib_name}{DLL_SUFFIX}");
+    let exe_dir = std::env::current_exe().ok()?.parent()?.to_path_buf();
+    let mut candidates = vec![exe_dir.clone()];
+    if let Some(profile_dir) = exe_dir.parent() {
+        candidates.push(profile_dir.to_path_buf());
+        if let Some(target_dir) = profile_dir.parent() {
+            for profile in ["debug", "release"] {
+                candidates.push(target_dir.join(profile));
+                candidates.push(target_dir.join(profile).join("deps"));
+            }
+        }
+    }
+
+    candidates
+        .into_iter()
+        .map(|dir| dir.join(&file_name))
+        .find(|path| path.exists())
+}
@@ -0,0 +1,52 @@
+//! Helpers for the `phases=dim:tick:phase|...` traces the engine logs to record
+//! the order in which per-dimension phases ran.
+
+/// Extract `(dimension, tick, phase)` entries from a log line's `phases=` token.
+///
+/// Entries appear in the order they were logged; malformed entries are skipped.
+pub fn parse_phase_line(line: &str) -> Vec<(u32, u64, String)> {
+    let Some(trace) = line
+        .split_whitespace()
+        .find_map(|token| token.strip_prefix("phases="))
+    else {
+        return Vec::new();
+    };
+
+    trace
+        .trim_end_matches([',', ';'])
+        .split('|')
+        .filter_map(|entry| {
+            let mut parts = entry.splitn(3, ':');
+            let dimension = parts.next()?.parse().ok()?;
+            let tick = parts.next()?.parse().ok()?;
+            let phase = parts.next().filter(|phase| !phase.is_empty())?;
+            Some((dimension, tick, phase.to_string()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_phase_line;
+
+    #[test]
+    fn parses_entries_in_order() {
+        assert_eq!(
+            parse_phase_line("tick 3 phases=0:3:sensors|1:3:physics|0:3:physics,"),
+            vec![
+                (0, 3, "sensors".to_string()),
+                (1, 3, "physics".to_string()),
+                (0, 3, "physics".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn skips_malformed_entries() {
+        assert_eq!(
+            parse_phase_line("phases=0:x:sensors|2:5:|1:5:commit"),
+            vec![(1, 5, "commit".to_string())]
+        );
+        assert!(parse_phase_line("tick 3 world_hash=abc").is_empty());
+    }
+}
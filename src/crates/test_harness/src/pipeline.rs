@@ -0,0 +1,247 @@
+//! Batched requests over a dedicated connection.
+//!
+//! `Client::send` waits for each response before the next request can go
+//! out, so pipelined spawns and inspects bypass it: the batch opens its own
+//! connection, which the engine must accept next to the session's, and
+//! neither reuses nor re-dials the session's client. It writes up to
+//! `PIPELINE_DEPTH` request frames back to back, then drains responses into a
+//! map keyed by envelope id before topping the window up again. Event frames
+//! the engine pushes to the connection are skipped, so the session's
+//! collector still sees each event exactly once on the main connection; a
+//! frame that is neither fails the batch straight away.
+
+use std::collections::HashMap;
+use std::io::{self, BufWriter, ErrorKind, Write};
+use std::net::{SocketAddr, TcpStream};
+use std::time::{Duration, Instant};
+
+use phase_space_protocol::psip::{
+    RequestEnvelope, ResponseEnvelope, ServerEvent, ServerRequest, ServerResponse,
+};
+
+use crate::error::{HarnessError, HarnessResult};
+use crate::wire;
+
+/// Maximum number of requests kept in flight at once.
+const PIPELINE_DEPTH: usize = 32;
+
+/// How long a batch waits for the next response before giving up.
+const RESPONSE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Send `requests` to the engine at `address` and return the responses in
+/// request order.
+///
+/// Once a response satisfies `stop`, no further requests are sent; the ones
+/// already in flight are still answered, so the result may be shorter than
+/// `requests` but always covers a prefix of it.
+///
+/// Fails as a whole if the connection breaks, a frame exceeds
+/// `max_frame_size` or decodes as neither a response nor an event, or the
+/// engine stops answering for `RESPONSE_TIMEOUT`;
+/// per-request failures come back as `ServerResponse::Error` or a non-OK
+/// status like they do from `Client::send`.
+pub(crate) fn exchange(
+    address: SocketAddr,
+    requests: Vec<ServerRequest>,
+    max_frame_size: usize,
+    stop: impl Fn(&ServerResponse) -> bool,
+) -> HarnessResult<Vec<ServerResponse>> {
+    let total = requests.len();
+    if total == 0 {
+        return Ok(Vec::new());
+    }
+    let mut stream = TcpStream::connect_timeout(&address, RESPONSE_TIMEOUT)?;
+    stream.set_nodelay(true)?;
+    trace_event!(debug, %address, requests = total, "opened pipeline connection");
+
+    let mut requests = requests.into_iter().enumerate();
+    let mut in_flight: HashMap<u64, usize> = HashMap::new();
+    let mut responses: Vec<Option<ServerResponse>> = (0..total).map(|_| None).collect();
+    let mut received = 0;
+    let mut stopped = false;
+    while !in_flight.is_empty() || (!stopped && received < total) {
+        if !stopped && in_flight.len() < PIPELINE_DEPTH {
+            let mut writer = BufWriter::new(&mut stream);
+            for (index, payload) in requests.by_ref().take(PIPELINE_DEPTH - in_flight.len()) {
+                trace_event!(trace, request = ?payload, "sending pipelined request");
+                // Envelope ids only need to be unique on this connection.
+                let id = index as u64 + 1;
+                let frame = wire::encode_frame(&RequestEnvelope { id, payload })
+                    .map_err(io::Error::from)?;
+                writer.write_all(&frame)?;
+                in_flight.insert(id, index);
+            }
+            writer.flush()?;
+        }
+
+        let deadline = Instant::now() + RESPONSE_TIMEOUT;
+        let (index, payload) = loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                return Err(HarnessError::WaitTimeout {
+                    timeout: RESPONSE_TIMEOUT,
+                    what: format!("{} pipelined response(s)", total - received),
+                });
+            }
+            stream.set_read_timeout(Some(remaining))?;
+            let frame = match wire::read_frame(&mut stream, max_frame_size) {
+                Ok(frame) => frame,
+                Err(err) if matches!(err.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {
+                    continue
+                }
+                Err(err) => return Err(err.into()),
+            };
+            // Events are meant for the collector on the main connection; any
+            // other frame that is not a response would only stall the batch.
+            let envelope = match wire::decode_frame::<ResponseEnvelope>(&frame) {
+                Ok(envelope) => envelope,
+                Err(_) if wire::decode_frame::<ServerEvent>(&frame).is_ok() => continue,
+                Err(err) => {
+                    return Err(HarnessError::unexpected(format!(
+                        "undecodable pipelined response: {err}"
+                    )))
+                }
+            };
+            if let Some(index) = in_flight.remove(&envelope.id) {
+                break (index, envelope.payload);
+            }
+        };
+        stopped |= stop(&payload);
+        responses[index] = Some(payload);
+        received += 1;
+    }
+
+    Ok(responses.into_iter().flatten().collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+    use std::net::TcpListener;
+    use std::thread;
+
+    use phase_space_protocol::psip::ResponseStatus;
+
+    use super::*;
+
+    /// Read `count` request frames and answer them in reverse order, with an
+    /// event frame ahead of the answers.
+    fn answer_reversed(stream: &mut TcpStream, count: usize) {
+        let mut ids: Vec<u64> = (0..count)
+            .map(|_| {
+                let frame = wire::read_frame(stream, wire::DEFAULT_MAX_FRAME_SIZE).expect("frame");
+                wire::decode_frame::<RequestEnvelope>(&frame)
+                    .expect("request")
+                    .id
+            })
+            .collect();
+        let event = ServerEvent::Log {
+            message: "pushed between responses".to_string(),
+        };
+        wire::write_frame(stream, &wire::encode_frame(&event).expect("event")).expect("write");
+        ids.reverse();
+        for id in ids {
+            let response = ResponseEnvelope {
+                id,
+                payload: ServerResponse::Shutdown {
+                    status: ResponseStatus::Ok,
+                    message: Some(id.to_string()),
+                },
+            };
+            let frame = wire::encode_frame(&response).expect("response");
+            wire::write_frame(stream, &frame).expect("write");
+        }
+    }
+
+    #[test]
+    fn responses_come_back_in_request_order() {
+        let total = PIPELINE_DEPTH + 8;
+        let listener = TcpListener::bind("127.0.0.1:0").expect("bind");
+        let address = listener.local_addr().expect("address");
+        let server = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().expect("accept");
+            answer_reversed(&mut stream, PIPELINE_DEPTH);
+            answer_reversed(&mut stream, total - PIPELINE_DEPTH);
+        });
+
+        let requests = (0..total).map(|_| ServerRequest::List).collect();
+        let responses =
+            exchange(address, requests, wire::DEFAULT_MAX_FRAME_SIZE, |_| false).expect("exchange");
+        let ids: Vec<String> = responses
+            .into_iter()
+            .map(|response| match response {
+                ServerResponse::Shutdown {
+                    message: Some(id), ..
+                } => id,
+                other => panic!("unexpected response: {other:?}"),
+            })
+            .collect();
+        let expected: Vec<String> = (1..=total).map(|id| id.to_string()).collect();
+        assert_eq!(ids, expected);
+        server.join().expect("server thread");
+    }
+
+    #[test]
+    fn no_requests_are_sent_once_stop_matches() {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("bind");
+        let address = listener.local_addr().expect("address");
+        let server = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().expect("accept");
+            answer_reversed(&mut stream, PIPELINE_DEPTH);
+            let mut rest = Vec::new();
+            stream.read_to_end(&mut rest).expect("read to end");
+            rest
+        });
+
+        let requests = (0..PIPELINE_DEPTH + 8)
+            .map(|_| ServerRequest::List)
+            .collect();
+        let responses =
+            exchange(address, requests, wire::DEFAULT_MAX_FRAME_SIZE, |_| true).expect("exchange");
+        assert_eq!(responses.len(), PIPELINE_DEPTH);
+        assert!(server.join().expect("server thread").is_empty());
+    }
+
+    #[test]
+    fn undecodable_response_frame_fails_without_waiting() {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("bind");
+        let address = listener.local_addr().expect("address");
+        let server = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().expect("accept");
+            wire::read_frame(&mut stream, wire::DEFAULT_MAX_FRAME_SIZE).expect("request frame");
+            let frame = wire::encode_frame(&"neither a response nor an event").expect("frame");
+            wire::write_frame(&mut stream, &frame).expect("write");
+            // Keep the connection open so only the bad frame can end the batch.
+            let mut rest = Vec::new();
+            let _ = stream.read_to_end(&mut rest);
+        });
+
+        let started = Instant::now();
+        match exchange(address, vec![ServerRequest::List], 1024, |_| false) {
+            Err(HarnessError::UnexpectedResponse(_)) => {}
+            other => panic!("unexpected result: {other:?}"),
+        }
+        assert!(started.elapsed() < RESPONSE_TIMEOUT);
+        server.join().expect("server thread");
+    }
+
+    #[test]
+    fn oversized_response_frame_is_rejected() {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("bind");
+        let address = listener.local_addr().expect("address");
+        let server = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().expect("accept");
+            let mut request = [0u8; 4];
+            stream.read_exact(&mut request).expect("request frame");
+            stream
+                .write_all(&u32::MAX.to_be_bytes())
+                .expect("length prefix");
+        });
+
+        match exchange(address, vec![ServerRequest::List], 1024, |_| false) {
+            Err(HarnessError::Io(err)) => assert_eq!(err.kind(), ErrorKind::InvalidData),
+            other => panic!("unexpected result: {other:?}"),
+        }
+        server.join().expect("server thread");
+    }
+}
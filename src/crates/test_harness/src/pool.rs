@@ -0,0 +1,118 @@
+use std::path::PathBuf;
+use std::process::ExitStatus;
+use std::thread;
+
+use crate::config::EngineConfig;
+use crate::error::{HarnessError, HarnessResult};
+use crate::harness::{scratch_dir, EngineHarness, Session};
+
+/// Several engines started from one config template, for concurrency stress tests.
+///
+/// Each engine listens on its own ephemeral port and runs in a private
+/// temporary working directory that is removed when the pool is dropped.
+pub struct HarnessPool {
+    sessions: Vec<Session>,
+    dirs: Vec<PathBuf>,
+}
+
+impl HarnessPool {
+    /// Start `count` engines in parallel and attach a session to each.
+    ///
+    /// Bind addresses set on the template are ignored. If any engine fails to
+    /// start, the ones that did are shut down before the error naming the
+    /// failing instance is returned.
+    pub fn spawn(config: EngineConfig, count: usize) -> HarnessResult<Self> {
+        let mut pool = Self {
+            sessions: Vec::with_capacity(count),
+            dirs: Vec::with_capacity(count),
+        };
+        for index in 0..count {
+            pool.dirs.push(scratch_dir(&format!("pool-{index}"))?);
+        }
+
+        let dirs = &pool.dirs;
+        let results: Vec<HarnessResult<Session>> = thread::scope(|scope| {
+            let handles: Vec<_> = dirs
+                .iter()
+                .map(|dir| {
+                    let mut config = config.clone().with_working_directory(dir);
+                    config.bind_addr = None;
+                    scope.spawn(move || EngineHarness::spawn(config)?.attach())
+                })
+                .collect();
+            handles
+                .into_iter()
+                .map(|handle| handle.join().expect("pool spawn thread panicked"))
+                .collect()
+        });
+
+        let mut failure = None;
+        for (index, result) in results.into_iter().enumerate() {
+            match result {
+                Ok(session) => pool.sessions.push(session),
+                Err(err) if failure.is_none() => {
+                    failure = Some(HarnessError::PoolSpawn {
+                        index,
+                        source: Box::new(err),
+                    })
+                }
+                Err(_) => {}
+            }
+        }
+
+        match failure {
+            Some(err) => {
+                // Stop the engines that did start; dropping the pool then
+                // removes every directory.
+                let _ = pool.shutdown();
+                Err(err)
+            }
+            None => Ok(pool),
+        }
+    }
+
+    /// Sessions in instance order.
+    pub fn sessions(&self) -> &[Session] {
+        &self.sessions
+    }
+
+    /// Mutable access to the sessions, e.g. to advance ticks.
+    pub fn sessions_mut(&mut self) -> &mut [Session] {
+        &mut self.sessions
+    }
+
+    /// Number of engines in the pool.
+    pub fn len(&self) -> usize {
+        self.sessions.len()
+    }
+
+    /// Whether the pool holds no engines.
+    pub fn is_empty(&self) -> bool {
+        self.sessions.is_empty()
+    }
+
+    /// Gracefully shut every engine down in parallel, returning results in instance order.
+    pub fn shutdown(mut self) -> Vec<HarnessResult<Option<ExitStatus>>> {
+        let sessions = std::mem::take(&mut self.sessions);
+        thread::scope(|scope| {
+            let handles: Vec<_> = sessions
+                .into_iter()
+                .map(|session| scope.spawn(move || session.shutdown()))
+                .collect();
+            handles
+                .into_iter()
+                .map(|handle| handle.join().expect("pool shutdown thread panicked"))
+                .collect()
+        })
+    }
+}
+
+impl Drop for HarnessPool {
+    fn drop(&mut self) {
+        // Stop the engines before removing the directories they run in.
+        self.sessions.clear();
+        for dir in &self.dirs {
+            let _ = std::fs::remove_dir_all(dir);
+        }
+    }
+}
@@ -0,0 +1,165 @@
+//! The engine together with every process it starts: its own process group on
+//! Unix, a Job Object on Windows.
+
+use std::io;
+use std::process::{Child, Command};
+
+/// Handle used to kill the engine's whole process tree at once.
+#[derive(Debug)]
+pub(crate) struct ProcessTree {
+    /// Group id, equal to the engine's pid as it leads the group.
+    #[cfg(unix)]
+    pgid: libc::pid_t,
+    /// Job the engine was assigned to; closing it kills what is left.
+    #[cfg(windows)]
+    job: windows_sys::Win32::Foundation::HANDLE,
+}
+
+// SAFETY: a job handle is a kernel object reference that may be used and
+// closed from any thread.
+#[cfg(windows)]
+unsafe impl Send for ProcessTree {}
+// SAFETY: see `Send`; the handle is never mutated after creation.
+#[cfg(windows)]
+unsafe impl Sync for ProcessTree {}
+
+impl ProcessTree {
+    /// Make the engine lead its own process group once spawned (Unix).
+    pub(crate) fn configure(cmd: &mut Command) {
+        #[cfg(unix)]
+        {
+            use std::os::unix::process::CommandExt;
+            cmd.process_group(0);
+        }
+        #[cfg(not(unix))]
+        let _ = cmd;
+    }
+
+    /// Track the tree rooted at the freshly spawned `child`, which must have
+    /// been started from a `Command` passed to `configure`.
+    ///
+    /// On Windows the engine is assigned to a new Job Object that kills its
+    /// members when closed; processes it starts before the assignment escape
+    /// the job. Returns `None` on platforms without either mechanism.
+    pub(crate) fn adopt(child: &Child) -> io::Result<Option<Self>> {
+        #[cfg(unix)]
+        {
+            let pgid = libc::pid_t::try_from(child.id())
+                .map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))?;
+            Ok(Some(Self { pgid }))
+        }
+        #[cfg(windows)]
+        {
+            Self::assign_job(child).map(Some)
+        }
+        #[cfg(not(any(unix, windows)))]
+        {
+            let _ = child;
+            Ok(None)
+        }
+    }
+
+    #[cfg(windows)]
+    fn assign_job(child: &Child) -> io::Result<Self> {
+        use std::os::windows::io::AsRawHandle;
+
+        use windows_sys::Win32::Foundation::CloseHandle;
+        use windows_sys::Win32::System::JobObjects::{
+            AssignProcessToJobObject, CreateJobObjectW, JobObjectExtendedLimitInformation,
+            SetInformationJobObject, JOBOBJECT_EXTENDED_LIMIT_INFORMATION,
+            JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE,
+        };
+
+        // SAFETY: null attributes and name create an anonymous job.
+        let job = unsafe { CreateJobObjectW(std::ptr::null(), std::ptr::null()) };
+        if job.is_null() {
+            return Err(io::Error::last_os_error());
+        }
+        // SAFETY: an all-zero limit structure is valid.
+        let mut limits: JOBOBJECT_EXTENDED_LIMIT_INFORMATION = unsafe { std::mem::zeroed() };
+        limits.BasicLimitInformation.LimitFlags = JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE;
+        // SAFETY: `job` is a valid job handle, `limits` outlives the call and
+        // the size matches the information class.
+        let configured = unsafe {
+            SetInformationJobObject(
+                job,
+                JobObjectExtendedLimitInformation,
+                std::ptr::from_ref(&limits).cast(),
+                std::mem::size_of_val(&limits) as u32,
+            )
+        };
+        // SAFETY: both handles are valid; the child's stays owned by `child`.
+        if configured == 0 || unsafe { AssignProcessToJobObject(job, child.as_raw_handle()) } == 0 {
+            let err = io::Error::last_os_error();
+            // SAFETY: `job` is valid and not used afterwards.
+            unsafe { CloseHandle(job) };
+            return Err(err);
+        }
+        Ok(Self { job })
+    }
+
+    /// Kill every process in the tree.
+    ///
+    /// On Unix the engine must not have been reaped yet, as its pid doubles
+    /// as the group id.
+    pub(crate) fn kill(&self) {
+        #[cfg(unix)]
+        self.signal(libc::SIGKILL);
+        #[cfg(windows)]
+        // SAFETY: `job` stays valid until `self` is dropped.
+        unsafe {
+            windows_sys::Win32::System::JobObjects::TerminateJobObject(self.job, 1);
+        }
+    }
+
+    /// Send `signal` to every process in the group; returns whether it was
+    /// delivered.
+    #[cfg(unix)]
+    pub(crate) fn signal(&self, signal: libc::c_int) -> bool {
+        // SAFETY: `kill` has no memory-safety preconditions; a group that no
+        // longer exists just yields `ESRCH`.
+        unsafe { libc::kill(-self.pgid, signal) == 0 }
+    }
+}
+
+#[cfg(windows)]
+impl Drop for ProcessTree {
+    fn drop(&mut self) {
+        // SAFETY: `job` is valid and closed exactly once. The job kills
+        // whatever is still in it once its last handle is closed.
+        unsafe {
+            windows_sys::Win32::Foundation::CloseHandle(self.job);
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use std::io::Read;
+    use std::os::unix::process::ExitStatusExt;
+    use std::process::Stdio;
+
+    use super::*;
+
+    #[test]
+    fn kill_reaches_processes_the_engine_started() {
+        let mut cmd = Command::new("sh");
+        cmd.args(["-c", "sleep 30 & echo started; wait"])
+            .stdout(Stdio::piped());
+        ProcessTree::configure(&mut cmd);
+        let mut child = cmd.spawn().expect("sh should run");
+        let tree = ProcessTree::adopt(&child)
+            .expect("adopt")
+            .expect("unix has process groups");
+
+        let mut stdout = child.stdout.take().expect("stdout is piped");
+        let mut started = [0u8; 8];
+        stdout.read_exact(&mut started).expect("worker started");
+        tree.kill();
+        let status = child.wait().expect("wait");
+        assert_eq!(status.signal(), Some(libc::SIGKILL));
+        // The worker shares the pipe, so EOF means it is gone as well.
+        let mut rest = Vec::new();
+        stdout.read_to_end(&mut rest).expect("read to end");
+    }
+}
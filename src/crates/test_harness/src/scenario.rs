@@ -0,0 +1,382 @@
+//! Scenario documents in the engine's `--scenario` input-log format, plus a
+//! builder for writing pre-seeded scenario files.
+
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use phase_space_protocol::psip::EntityParameters;
+
+use crate::config::{EngineConfig, SpawnSpec};
+use crate::error::{HarnessError, HarnessResult};
+
+/// Scenario document in the engine's `--scenario` input-log format.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct InputLog {
+    pub dt_seconds: f64,
+    pub total_ticks: u64,
+    /// Ticks at which the engine snapshots state.
+    pub checkpoints: Vec<u64>,
+    pub entities: Vec<EntitySeed>,
+}
+
+/// Entity entry of a scenario document.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EntitySeed {
+    pub name: String,
+    #[serde(default)]
+    pub dimension: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transform: Option<TransformSeed>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub velocity: Option<VelocitySeed>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mass_kg: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub interior_dimension: Option<u32>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TransformSeed {
+    pub x: f64,
+    pub y: f64,
+    #[serde(default)]
+    pub z: f64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct VelocitySeed {
+    pub dx: f64,
+    pub dy: f64,
+    #[serde(default)]
+    pub dz: f64,
+}
+
+impl EntitySeed {
+    /// Create a seed of the given entity type in dimension 0.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            dimension: 0,
+            transform: None,
+            velocity: None,
+            mass_kg: None,
+            interior_dimension: None,
+        }
+    }
+
+    /// Place the entity in a dimension.
+    pub fn in_dimension(mut self, dimension: u32) -> Self {
+        self.dimension = dimension;
+        self
+    }
+
+    /// Set the planar starting position.
+    pub fn at(mut self, x: f64, y: f64) -> Self {
+        self.transform = Some(TransformSeed { x, y, z: 0.0 });
+        self
+    }
+
+    /// Set the planar starting velocity.
+    pub fn with_velocity(mut self, dx: f64, dy: f64) -> Self {
+        self.velocity = Some(VelocitySeed { dx, dy, dz: 0.0 });
+        self
+    }
+
+    /// Set the entity mass in kilograms.
+    pub fn with_mass(mut self, mass_kg: f64) -> Self {
+        self.mass_kg = Some(mass_kg);
+        self
+    }
+
+    /// Convert the seed into a spawn request; the protocol is planar, so `z` is dropped.
+    pub fn to_spawn_spec(&self) -> SpawnSpec {
+        SpawnSpec::new(self.name.clone())
+            .with_parameters(EntityParameters {
+                position: self.transform.as_ref().map(|t| (t.x, t.y)),
+                velocity: self.velocity.as_ref().map(|v| (v.dx, v.dy)),
+                mass: self.mass_kg,
+            })
+            .in_dimension(self.dimension)
+    }
+}
+
+/// Builder for scenario files passed to the engine through `--scenario`.
+#[derive(Debug, Clone)]
+pub struct Builder {
+    dt_seconds: f64,
+    total_ticks: u64,
+    checkpoint_interval: Option<u64>,
+    checkpoint_ticks: Option<Vec<u64>>,
+    world_seed: Option<u64>,
+    context_plugin: Option<PathBuf>,
+    entities: Vec<EntitySeed>,
+}
+
+impl Default for Builder {
+    fn default() -> Self {
+        Self {
+            dt_seconds: 1.0,
+            total_ticks: 0,
+            checkpoint_interval: None,
+            checkpoint_ticks: None,
+            world_seed: None,
+            context_plugin: None,
+            entities: Vec::new(),
+        }
+    }
+}
+
+impl Builder {
+    /// Start an empty scenario with a one-second tick.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Simulated seconds per tick.
+    pub fn with_dt_seconds(mut self, dt_seconds: f64) -> Self {
+        self.dt_seconds = dt_seconds;
+        self
+    }
+
+    /// Number of ticks the scenario runs for.
+    pub fn with_total_ticks(mut self, total_ticks: u64) -> Self {
+        self.total_ticks = total_ticks;
+        self
+    }
+
+    /// Snapshot every `interval` ticks, always including the final tick; with neither an
+    /// interval nor explicit ticks only the first and last tick are checkpoints.
+    pub fn with_checkpoint_interval(mut self, interval: u64) -> Self {
+        self.checkpoint_interval = Some(interval);
+        self
+    }
+
+    /// Snapshot at exactly these ticks, in any order; duplicates are dropped.
+    ///
+    /// Unlike the interval, an explicit list does not force the final tick in.
+    /// When both are set the checkpoints are the union of the two.
+    pub fn with_checkpoint_ticks(mut self, ticks: Vec<u64>) -> Self {
+        self.checkpoint_ticks = Some(ticks);
+        self
+    }
+
+    /// World seed to launch the engine with.
+    pub fn with_world_seed(mut self, seed: u64) -> Self {
+        self.world_seed = Some(seed);
+        self
+    }
+
+    /// Context plugin to load alongside the scenario.
+    pub fn with_context_plugin(mut self, path: impl Into<PathBuf>) -> Self {
+        self.context_plugin = Some(path.into());
+        self
+    }
+
+    /// Add an entity to seed.
+    pub fn with_entity(mut self, entity: EntitySeed) -> Self {
+        self.entities.push(entity);
+        self
+    }
+
+    /// Validate the settings and assemble the scenario.
+    pub fn build(self) -> HarnessResult<BuiltScenario> {
+        let mut problems = Vec::new();
+        if !(self.dt_seconds.is_finite() && self.dt_seconds > 0.0) {
+            problems.push(format!(
+                "dt_seconds must be positive and finite, got {}",
+                self.dt_seconds
+            ));
+        }
+        for (index, entity) in self.entities.iter().enumerate() {
+            if entity.name.trim().is_empty() {
+                problems.push(format!("entity {index} has an empty name"));
+            }
+        }
+        for tick in self.checkpoint_ticks.iter().flatten() {
+            if *tick > self.total_ticks {
+                problems.push(format!(
+                    "checkpoint tick {tick} is past total_ticks {}",
+                    self.total_ticks
+                ));
+            }
+        }
+        if !problems.is_empty() {
+            return Err(HarnessError::InvalidScenario(problems));
+        }
+
+        let checkpoints = match self.checkpoint_ticks {
+            Some(mut ticks) => {
+                if self.checkpoint_interval.is_some() {
+                    ticks.extend(build_checkpoints(
+                        self.total_ticks,
+                        self.checkpoint_interval,
+                    ));
+                }
+                ticks.sort_unstable();
+                ticks.dedup();
+                ticks
+            }
+            None => build_checkpoints(self.total_ticks, self.checkpoint_interval),
+        };
+
+        Ok(BuiltScenario {
+            log: InputLog {
+                dt_seconds: self.dt_seconds,
+                total_ticks: self.total_ticks,
+                checkpoints,
+                entities: self.entities,
+            },
+            world_seed: self.world_seed,
+            context_plugin: self.context_plugin,
+        })
+    }
+}
+
+/// Scenario produced by [`Builder::build`].
+#[derive(Debug, Clone)]
+pub struct BuiltScenario {
+    log: InputLog,
+    world_seed: Option<u64>,
+    context_plugin: Option<PathBuf>,
+}
+
+impl BuiltScenario {
+    /// The document written by `write_json`.
+    pub fn log(&self) -> &InputLog {
+        &self.log
+    }
+
+    /// Seed `configure` passes to the engine, if the builder set one.
+    pub fn world_seed(&self) -> Option<u64> {
+        self.world_seed
+    }
+
+    /// Context plugin `configure` loads, if the builder set one.
+    pub fn context_plugin(&self) -> Option<&PathBuf> {
+        self.context_plugin.as_ref()
+    }
+
+    /// Write the scenario document as pretty-printed JSON.
+    ///
+    /// Errors name `path`.
+    pub fn write_json(&self, path: &Path) -> HarnessResult<()> {
+        let write = || -> io::Result<()> {
+            let mut writer = BufWriter::new(File::create(path)?);
+            serde_json::to_writer_pretty(&mut writer, &self.log)?;
+            writer.flush()
+        };
+        write().map_err(|err| {
+            let message = format!("failed to write {}: {err}", path.display());
+            HarnessError::Io(io::Error::new(err.kind(), message))
+        })
+    }
+
+    /// Point `config` at a scenario file written to `path`, applying the seed and plugin.
+    pub fn configure(&self, config: EngineConfig, path: &Path) -> EngineConfig {
+        let mut config = config.with_scenario_path(path);
+        if let Some(seed) = self.world_seed {
+            config = config.with_world_seed(seed);
+        }
+        if let Some(plugin) = &self.context_plugin {
+            config = config.with_context_plugin(plugin.clone());
+        }
+        config
+    }
+}
+
+/// Checkpoint ticks for a run: every `interval` ticks from 0, always ending on
+/// `total_ticks`; just the first and last tick without a (non-zero) interval.
+fn build_checkpoints(total_ticks: u64, interval: Option<u64>) -> Vec<u64> {
+    match interval.filter(|value| *value > 0) {
+        Some(step) => {
+            let mut checkpoints = Vec::new();
+            let mut tick = 0;
+            while tick <= total_ticks {
+                checkpoints.push(tick);
+                match tick.checked_add(step) {
+                    Some(next) => tick = next,
+                    None => break,
+                }
+            }
+            if checkpoints.last().copied() != Some(total_ticks) {
+                checkpoints.push(total_ticks);
+            }
+            checkpoints
+        }
+        None if total_ticks == 0 => vec![0],
+        None => vec![0, total_ticks],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checkpoints_follow_interval_and_end_on_last_tick() {
+        assert_eq!(build_checkpoints(6, Some(2)), vec![0, 2, 4, 6]);
+        assert_eq!(build_checkpoints(7, Some(3)), vec![0, 3, 6, 7]);
+        assert_eq!(build_checkpoints(6, None), vec![0, 6]);
+        assert_eq!(build_checkpoints(6, Some(0)), vec![0, 6]);
+        assert_eq!(build_checkpoints(0, None), vec![0]);
+        assert_eq!(
+            build_checkpoints(u64::MAX, Some(u64::MAX)),
+            vec![0, u64::MAX]
+        );
+    }
+
+    #[test]
+    fn explicit_checkpoints_are_normalized_and_bounded() {
+        let checkpoints = |builder: Builder| builder.build().unwrap().log().checkpoints.clone();
+        let builder = Builder::new().with_total_ticks(10);
+
+        assert_eq!(
+            checkpoints(builder.clone().with_checkpoint_ticks(vec![5, 1, 5, 3])),
+            vec![1, 3, 5]
+        );
+        assert_eq!(
+            checkpoints(
+                builder
+                    .clone()
+                    .with_checkpoint_interval(4)
+                    .with_checkpoint_ticks(vec![7])
+            ),
+            vec![0, 4, 7, 8, 10]
+        );
+        assert!(matches!(
+            builder.with_checkpoint_ticks(vec![3, 11]).build(),
+            Err(HarnessError::InvalidScenario(problems)) if problems.len() == 1
+        ));
+    }
+
+    #[test]
+    fn build_rejects_invalid_settings() {
+        let err = Builder::new()
+            .with_dt_seconds(0.0)
+            .with_entity(EntitySeed::new(" "))
+            .build()
+            .unwrap_err();
+        match err {
+            HarnessError::InvalidScenario(problems) => assert_eq!(problems.len(), 2),
+            other => panic!("unexpected error: {other}"),
+        }
+    }
+
+    #[test]
+    fn write_json_errors_name_the_path() {
+        let scenario = Builder::new().build().expect("default scenario is valid");
+        let path = std::env::temp_dir()
+            .join("phase-space-harness-missing-dir")
+            .join("scenario.json");
+        match scenario.write_json(&path) {
+            Err(HarnessError::Io(err)) => {
+                assert!(err.to_string().contains("scenario.json"), "{err}")
+            }
+            other => panic!("unexpected result: {other:?}"),
+        }
+    }
+}
@@ -0,0 +1,104 @@
+//! Key/value access to the free-form `message` of telemetry events.
+
+use std::collections::BTreeMap;
+
+/// `key=value` pairs parsed from a telemetry message such as
+/// `tick 4 world_hash=abc123 phases=0:4:physics`.
+///
+/// Tokens are separated by whitespace and trailing `,`/`;`/`:` separators
+/// are ignored. A bare `tick N` is read as `tick=N`. When a key repeats, the
+/// first non-empty value wins; tokens without `=` are skipped.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TelemetryFields {
+    fields: BTreeMap<String, String>,
+}
+
+impl TelemetryFields {
+    /// Parse the pairs out of `message`.
+    pub fn parse(message: &str) -> Self {
+        let mut fields = BTreeMap::new();
+        let mut tokens = message.split_whitespace().map(trim_separators);
+        while let Some(token) = tokens.next() {
+            let (key, value) = match token.split_once('=') {
+                Some(pair) => pair,
+                None if token == "tick" => match tokens.next() {
+                    Some(value) if value.parse::<u64>().is_ok() => ("tick", value),
+                    _ => continue,
+                },
+                None => continue,
+            };
+            if !key.is_empty() && !value.is_empty() {
+                fields
+                    .entry(key.to_string())
+                    .or_insert_with(|| value.to_string());
+            }
+        }
+        Self { fields }
+    }
+
+    /// Raw value for `key`.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.fields.get(key).map(String::as_str)
+    }
+
+    /// Value for `key` parsed as an unsigned integer.
+    pub fn get_u64(&self, key: &str) -> Option<u64> {
+        self.get(key)?.parse().ok()
+    }
+
+    /// Value for `key` parsed as a signed integer.
+    pub fn get_i64(&self, key: &str) -> Option<i64> {
+        self.get(key)?.parse().ok()
+    }
+
+    /// Value for `key` parsed as a float.
+    pub fn get_f64(&self, key: &str) -> Option<f64> {
+        self.get(key)?.parse().ok()
+    }
+
+    /// Whether the message carried `key`.
+    pub fn contains(&self, key: &str) -> bool {
+        self.fields.contains_key(key)
+    }
+
+    /// All pairs, sorted by key.
+    pub fn as_map(&self) -> &BTreeMap<String, String> {
+        &self.fields
+    }
+
+    /// Number of distinct keys.
+    pub fn len(&self) -> usize {
+        self.fields.len()
+    }
+
+    /// Whether the message carried no pairs at all.
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+}
+
+fn trim_separators(token: &str) -> &str {
+    token.trim_end_matches([',', ';', ':'])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TelemetryFields;
+
+    #[test]
+    fn parses_pairs_and_bare_tick() {
+        let fields =
+            TelemetryFields::parse("entity 2 tick 9 [probe]: world_hash=ff00, x=-1.5; tick=10");
+        assert_eq!(fields.get_u64("tick"), Some(9), "first tick wins");
+        assert_eq!(fields.get("world_hash"), Some("ff00"));
+        assert_eq!(fields.get_f64("x"), Some(-1.5));
+        assert_eq!(fields.get_i64("x"), None);
+        assert_eq!(fields.len(), 3);
+
+        let fields = TelemetryFields::parse("phases=0:3:sensors|1:3:physics world_hash= mode=idle");
+        assert_eq!(fields.get("phases"), Some("0:3:sensors|1:3:physics"));
+        assert!(!fields.contains("world_hash"), "empty values are skipped");
+        assert_eq!(fields.get("mode"), Some("idle"));
+        assert!(TelemetryFields::parse("tick soon").is_empty());
+    }
+}
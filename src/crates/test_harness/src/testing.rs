@@ -0,0 +1,697 @@
+//! In-process stand-in for the engine's protocol server, for testing harness
+//! code without launching the real binary.
+//!
+//! [`FakeEngine`] accepts any number of connections, answers spawn, list,
+//! inspect and shutdown requests from an in-memory entity table, and emits
+//! telemetry to every connection every 10 ms plus one tick per request. The
+//! `fake_engine` binary wraps it. [`FakeEngineConfig`] controls the tick
+//! cadence, can make telemetry per-entity and seeded, and injects failures
+//! and latency for exercising harness error paths.
+
+use std::collections::{BTreeMap, HashSet};
+use std::io::{self, ErrorKind};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use phase_space_protocol::psip::{
+    EntityParameters, EntityRecord, EntitySummary, RequestEnvelope, ResponseEnvelope,
+    ResponseStatus, ServerEvent, ServerRequest, ServerResponse,
+};
+
+use crate::error::HarnessResult;
+use crate::harness::Session;
+use crate::wire::{self, DEFAULT_MAX_FRAME_SIZE};
+
+/// Background tick period unless overridden with `with_tick_interval`.
+const DEFAULT_TICK_INTERVAL: Duration = Duration::from_millis(10);
+
+/// How long `advance_manual_ticks` waits for the requested ticks.
+const MANUAL_TICK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How often the telemetry thread checks for shutdown when it has no ticks
+/// of its own to emit.
+const IDLE_POLL: Duration = Duration::from_millis(10);
+
+/// Behaviour knobs for a [`FakeEngine`], mostly for injecting faults.
+#[derive(Debug, Clone)]
+pub struct FakeEngineConfig {
+    /// Request frames longer than this are rejected with `InvalidData`.
+    pub max_frame_size: usize,
+    /// Answer every spawn after the first `n` with `ResponseStatus::Error`.
+    pub spawn_failure_after: Option<usize>,
+    /// Entity ids that inspect reports as `ResponseStatus::NotFound`, even
+    /// when they exist.
+    pub inspect_not_found_ids: HashSet<u64>,
+    /// Delay before answering each request.
+    pub response_latency: Duration,
+    /// Fixed period of background ticks; `None` emits no background ticks.
+    pub tick_interval: Option<Duration>,
+    /// Advance one tick, with a telemetry event, for every request answered.
+    pub tick_per_request: bool,
+    /// Emit telemetry round-robin across all entities, moving each to a
+    /// position derived from this seed, the entity id and the tick.
+    pub seed: Option<u64>,
+}
+
+impl Default for FakeEngineConfig {
+    fn default() -> Self {
+        Self {
+            max_frame_size: DEFAULT_MAX_FRAME_SIZE,
+            spawn_failure_after: None,
+            inspect_not_found_ids: HashSet::new(),
+            response_latency: Duration::ZERO,
+            tick_interval: Some(DEFAULT_TICK_INTERVAL),
+            tick_per_request: true,
+            seed: None,
+        }
+    }
+}
+
+impl FakeEngineConfig {
+    /// Let the first `successes` spawns through and fail every later one.
+    pub fn with_spawn_failure_after(mut self, successes: usize) -> Self {
+        self.spawn_failure_after = Some(successes);
+        self
+    }
+
+    /// Report `entity_id` as missing from inspect requests.
+    pub fn with_inspect_not_found(mut self, entity_id: u64) -> Self {
+        self.inspect_not_found_ids.insert(entity_id);
+        self
+    }
+
+    /// Sleep for `latency` before answering each request.
+    pub fn with_response_latency(mut self, latency: Duration) -> Self {
+        self.response_latency = latency;
+        self
+    }
+
+    /// Emit a background tick every `interval`, measured from the previous
+    /// tick rather than from the last request.
+    pub fn with_tick_interval(mut self, interval: Duration) -> Self {
+        self.tick_interval = Some(interval);
+        self
+    }
+
+    /// Only advance on requests: each answered request is exactly one tick,
+    /// so tick counts depend on what the test sends rather than on timing.
+    pub fn manual_ticks(mut self) -> Self {
+        self.tick_interval = None;
+        self.tick_per_request = true;
+        self
+    }
+
+    /// Whether each answered request advances one tick.
+    pub fn with_tick_per_request(mut self, enabled: bool) -> Self {
+        self.tick_per_request = enabled;
+        self
+    }
+
+    /// Emit seeded per-entity telemetry; the same seed and request sequence
+    /// reproduce the same positions.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+}
+
+/// Builder for a fake engine served on a background thread.
+#[derive(Debug, Clone, Default)]
+pub struct FakeEngine {
+    config: FakeEngineConfig,
+}
+
+impl FakeEngine {
+    /// Fake engine with default settings.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start a fake engine with default settings on an ephemeral loopback port.
+    pub fn start() -> io::Result<(SocketAddr, FakeEngineHandle)> {
+        Self::new().spawn()
+    }
+
+    /// Fake engine with the given behaviour.
+    pub fn with_config(config: FakeEngineConfig) -> Self {
+        Self { config }
+    }
+
+    /// Reject request frames longer than `bytes` with `InvalidData` before
+    /// allocating for them.
+    pub fn with_max_frame_size(mut self, bytes: usize) -> Self {
+        self.config.max_frame_size = bytes;
+        self
+    }
+
+    /// Bind an ephemeral loopback port and serve on a background thread.
+    ///
+    /// Point `EngineHarness::connect` at the returned address.
+    pub fn spawn(self) -> io::Result<(SocketAddr, FakeEngineHandle)> {
+        self.spawn_at(SocketAddr::from(([127, 0, 0, 1], 0)))
+    }
+
+    /// Bind `addr` and serve on a background thread.
+    pub fn spawn_at(self, addr: SocketAddr) -> io::Result<(SocketAddr, FakeEngineHandle)> {
+        let listener = TcpListener::bind(addr)?;
+        let addr = listener.local_addr()?;
+        let running = Arc::new(AtomicBool::new(true));
+        let thread = {
+            let running = running.clone();
+            thread::spawn(move || serve(listener, self.config, running))
+        };
+        Ok((
+            addr,
+            FakeEngineHandle {
+                running,
+                thread: Some(thread),
+            },
+        ))
+    }
+}
+
+/// Control handle for a running [`FakeEngine`]; stops it when dropped.
+#[derive(Debug)]
+pub struct FakeEngineHandle {
+    running: Arc<AtomicBool>,
+    thread: Option<thread::JoinHandle<io::Result<()>>>,
+}
+
+impl FakeEngineHandle {
+    /// Whether the engine is still serving; it stops on its own after a
+    /// shutdown request or once every client that sent a request has
+    /// disconnected.
+    pub fn is_running(&self) -> bool {
+        self.thread
+            .as_ref()
+            .is_some_and(|thread| !thread.is_finished())
+    }
+
+    /// Block until the engine stops on its own and return how serving ended.
+    pub fn join(mut self) -> io::Result<()> {
+        self.wait()
+    }
+
+    /// Stop serving and wait for the engine thread to finish.
+    pub fn stop(mut self) -> io::Result<()> {
+        self.running.store(false, Ordering::SeqCst);
+        self.wait()
+    }
+
+    fn wait(&mut self) -> io::Result<()> {
+        match self.thread.take() {
+            Some(thread) => thread
+                .join()
+                .unwrap_or_else(|_| Err(io::Error::other("fake engine thread panicked"))),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Drop for FakeEngineHandle {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::SeqCst);
+        let _ = self.wait();
+    }
+}
+
+/// Advance a session on an engine that only ticks on requests, such as one
+/// configured with [`FakeEngineConfig::manual_ticks`], by exactly `ticks`.
+///
+/// Sends one list request per tick and waits for the resulting telemetry.
+/// Unlike `Session::advance_ticks` it neither records telemetry history nor
+/// restarts a crashed engine.
+pub fn advance_manual_ticks(session: &mut Session, ticks: u64) -> HarnessResult<()> {
+    let target = session.current_tick().saturating_add(ticks);
+    for _ in 0..ticks {
+        session.send(ServerRequest::List)?;
+    }
+    session.wait_for_tick(target, MANUAL_TICK_TIMEOUT)?;
+    Ok(())
+}
+
+/// Accept connections and answer requests on each until shutdown, or until
+/// every client that sent a request has disconnected. The first connection
+/// error stops the engine and is returned.
+fn serve(
+    listener: TcpListener,
+    config: FakeEngineConfig,
+    running: Arc<AtomicBool>,
+) -> io::Result<()> {
+    // Poll for connections so a handle can stop an engine nobody connected to.
+    listener.set_nonblocking(true)?;
+    let (event_tx, event_rx) = mpsc::channel();
+    let state = Arc::new(EngineState {
+        config,
+        entities: Arc::new(Mutex::new(BTreeMap::new())),
+        next_id: AtomicU64::new(1),
+        running: running.clone(),
+        event_tx,
+        tick_counter: Arc::new(AtomicU64::new(0)),
+        spawn_count: AtomicUsize::new(0),
+        subscribers: Arc::new(Mutex::new(Vec::new())),
+        open_connections: AtomicUsize::new(0),
+    });
+
+    let telemetry_handle = spawn_telemetry_thread(
+        state.subscribers.clone(),
+        event_rx,
+        state.config.tick_interval,
+        state.config.seed,
+        running.clone(),
+        state.tick_counter.clone(),
+        state.entities.clone(),
+    );
+
+    let mut connections: Vec<thread::JoinHandle<io::Result<()>>> = Vec::new();
+    let mut result = Ok(());
+    while running.load(Ordering::SeqCst) {
+        match listener.accept() {
+            Ok((stream, _)) => {
+                state.open_connections.fetch_add(1, Ordering::SeqCst);
+                let state = state.clone();
+                connections.push(thread::spawn(move || {
+                    let mut requested = false;
+                    let result = serve_connection(stream, &state, &mut requested);
+                    let remaining = state.open_connections.fetch_sub(1, Ordering::SeqCst) - 1;
+                    if result.is_err() || (requested && remaining == 0) {
+                        state.running.store(false, Ordering::SeqCst);
+                    }
+                    result
+                }));
+            }
+            Err(err) if err.kind() == ErrorKind::WouldBlock => {
+                thread::sleep(Duration::from_millis(10));
+            }
+            Err(err) => {
+                result = Err(err);
+                break;
+            }
+        }
+    }
+
+    running.store(false, Ordering::SeqCst);
+    for connection in connections {
+        let outcome = connection
+            .join()
+            .unwrap_or_else(|_| Err(io::Error::other("fake engine connection panicked")));
+        if result.is_ok() {
+            result = outcome;
+        }
+    }
+    drop(state);
+    let _ = telemetry_handle.join();
+    result
+}
+
+/// Answer requests on one connection until it closes or the engine stops,
+/// setting `requested` once a request arrives.
+fn serve_connection(
+    mut stream: TcpStream,
+    state: &EngineState,
+    requested: &mut bool,
+) -> io::Result<()> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(Duration::from_millis(50)))?;
+    // Responses and broadcast events share one writer so frames never interleave.
+    let writer = Arc::new(Mutex::new(stream.try_clone()?));
+    if let Ok(mut subscribers) = state.subscribers.lock() {
+        subscribers.push(writer.clone());
+    }
+
+    loop {
+        match wire::read_frame(&mut stream, state.config.max_frame_size) {
+            Ok(frame) => {
+                *requested = true;
+                let envelope: RequestEnvelope = wire::decode_frame(&frame)?;
+                let response = handle_request(envelope, state);
+                let framed = wire::encode_frame(&response)?;
+                let mut writer = writer
+                    .lock()
+                    .map_err(|_| io::Error::other("fake engine writer poisoned"))?;
+                wire::write_frame(&mut *writer, &framed)?;
+                if !state.running.load(Ordering::SeqCst) {
+                    return Ok(());
+                }
+            }
+            Err(err) if matches!(err.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {
+                if !state.running.load(Ordering::SeqCst) {
+                    return Ok(());
+                }
+            }
+            Err(err) if is_disconnect(&err) => return Ok(()),
+            // An oversized frame stops the engine with the error.
+            Err(err) => return Err(err),
+        }
+    }
+}
+
+/// Whether `err` means the client hung up between frames.
+fn is_disconnect(err: &io::Error) -> bool {
+    matches!(
+        err.kind(),
+        ErrorKind::UnexpectedEof
+            | ErrorKind::ConnectionReset
+            | ErrorKind::ConnectionAborted
+            | ErrorKind::BrokenPipe
+    )
+}
+
+/// Write ends of every open connection, which all receive telemetry.
+type Subscribers = Arc<Mutex<Vec<Arc<Mutex<TcpStream>>>>>;
+
+/// Everything the connections share with each other and the telemetry
+/// thread, plus the counters fault injection keys off.
+struct EngineState {
+    config: FakeEngineConfig,
+    entities: Arc<Mutex<BTreeMap<u64, EntityRecord>>>,
+    next_id: AtomicU64,
+    running: Arc<AtomicBool>,
+    event_tx: mpsc::Sender<ServerEvent>,
+    tick_counter: Arc<AtomicU64>,
+    spawn_count: AtomicUsize,
+    subscribers: Subscribers,
+    open_connections: AtomicUsize,
+}
+
+fn handle_request(envelope: RequestEnvelope, state: &EngineState) -> ResponseEnvelope {
+    if !state.config.response_latency.is_zero() {
+        thread::sleep(state.config.response_latency);
+    }
+
+    let entities = &state.entities;
+    let response = match envelope.payload {
+        ServerRequest::Spawn {
+            entity_type,
+            parameters,
+            dimension,
+        } => {
+            let spawn_count = state.spawn_count.fetch_add(1, Ordering::SeqCst) + 1;
+            let failing = state
+                .config
+                .spawn_failure_after
+                .is_some_and(|successes| spawn_count > successes);
+            if failing {
+                // Echo the request back without registering anything.
+                ServerResponse::Spawned {
+                    status: ResponseStatus::Error,
+                    entity: EntitySummary {
+                        dimension: dimension.unwrap_or(0),
+                        entity_id: 0,
+                        kind: entity_type,
+                        position: parameters.position,
+                    },
+                }
+            } else {
+                let entity = register_entity(
+                    entities,
+                    &state.next_id,
+                    &entity_type,
+                    parameters,
+                    dimension,
+                );
+                ServerResponse::Spawned {
+                    status: ResponseStatus::Ok,
+                    entity,
+                }
+            }
+        }
+        ServerRequest::List => {
+            let list = entities
+                .lock()
+                .map(|map| {
+                    map.values()
+                        .map(|record| EntitySummary {
+                            dimension: record.dimension,
+                            entity_id: record.entity_id,
+                            kind: record.kind.clone(),
+                            position: record.position,
+                        })
+                        .collect()
+                })
+                .unwrap_or_default();
+
+            ServerResponse::Listed {
+                status: ResponseStatus::Ok,
+                entities: list,
+            }
+        }
+        ServerRequest::Inspect {
+            dimension: _,
+            entity_id,
+        } => {
+            let record = entities
+                .lock()
+                .ok()
+                .and_then(|map| map.get(&entity_id).cloned())
+                .filter(|_| !state.config.inspect_not_found_ids.contains(&entity_id));
+            let status = if record.is_some() {
+                ResponseStatus::Ok
+            } else {
+                ResponseStatus::NotFound
+            };
+            ServerResponse::InspectResult {
+                status,
+                entity: record,
+                message: None,
+            }
+        }
+        ServerRequest::Shutdown => {
+            state.running.store(false, Ordering::SeqCst);
+            ServerResponse::Shutdown {
+                status: ResponseStatus::Ok,
+                message: Some("shutdown requested".to_string()),
+            }
+        }
+    };
+
+    // Send a telemetry event for each request to keep tick counts advancing.
+    if state.config.tick_per_request {
+        let tick = state.tick_counter.fetch_add(1, Ordering::SeqCst) + 1;
+        let _ = state
+            .event_tx
+            .send(build_event(tick, state.config.seed, entities));
+    }
+
+    ResponseEnvelope {
+        id: envelope.id,
+        payload: response,
+    }
+}
+
+fn register_entity(
+    entities: &Arc<Mutex<BTreeMap<u64, EntityRecord>>>,
+    next_id: &AtomicU64,
+    entity_type: &str,
+    parameters: EntityParameters,
+    dimension: Option<u32>,
+) -> EntitySummary {
+    let id = next_id.fetch_add(1, Ordering::SeqCst);
+    let dimension_id = dimension.unwrap_or(0);
+    let record = EntityRecord {
+        dimension: dimension_id,
+        entity_id: id,
+        kind: entity_type.to_string(),
+        position: parameters.position,
+        velocity: parameters.velocity,
+        mass: parameters.mass,
+    };
+
+    if let Ok(mut map) = entities.lock() {
+        map.insert(id, record.clone());
+    }
+
+    EntitySummary {
+        dimension: dimension_id,
+        entity_id: id,
+        kind: entity_type.to_string(),
+        position: record.position,
+    }
+}
+
+/// Telemetry for `tick`: about the first entity, or with a seed, about each
+/// entity in turn after moving it to its seeded position.
+fn build_event(
+    tick: u64,
+    seed: Option<u64>,
+    entities: &Arc<Mutex<BTreeMap<u64, EntityRecord>>>,
+) -> ServerEvent {
+    let Ok(mut map) = entities.lock() else {
+        return unseeded_event(tick, 0);
+    };
+    let Some(seed) = seed.filter(|_| !map.is_empty()) else {
+        return unseeded_event(tick, map.keys().next().copied().unwrap_or(0));
+    };
+
+    let index = (tick.saturating_sub(1) % map.len() as u64) as usize;
+    let Some((&id, record)) = map.iter_mut().nth(index) else {
+        return unseeded_event(tick, 0);
+    };
+    let (x, y) = seeded_position(seed, id, tick);
+    record.position = Some((x, y));
+    ServerEvent::Telemetry {
+        id,
+        tick,
+        ship: record.kind.clone(),
+        message: format!("tick {tick} x={x:.6} y={y:.6}"),
+    }
+}
+
+fn unseeded_event(tick: u64, id: u64) -> ServerEvent {
+    ServerEvent::Telemetry {
+        id,
+        tick,
+        ship: "fake".to_string(),
+        message: format!("tick {tick}"),
+    }
+}
+
+/// Position in `[-100, 100)` on both axes, fixed by `(seed, id, tick)`.
+fn seeded_position(seed: u64, id: u64, tick: u64) -> (f64, f64) {
+    let base = splitmix64(seed ^ splitmix64(id ^ splitmix64(tick)));
+    let x = unit_interval(splitmix64(base)) * 200.0 - 100.0;
+    let y = unit_interval(splitmix64(base ^ 1)) * 200.0 - 100.0;
+    (x, y)
+}
+
+fn splitmix64(value: u64) -> u64 {
+    let mut z = value.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// Map the top 53 bits of `bits` onto `[0, 1)`.
+fn unit_interval(bits: u64) -> f64 {
+    (bits >> 11) as f64 / (1u64 << 53) as f64
+}
+
+fn spawn_telemetry_thread(
+    subscribers: Subscribers,
+    event_rx: mpsc::Receiver<ServerEvent>,
+    tick_interval: Option<Duration>,
+    seed: Option<u64>,
+    running: Arc<AtomicBool>,
+    tick_counter: Arc<AtomicU64>,
+    entities: Arc<Mutex<BTreeMap<u64, EntityRecord>>>,
+) -> thread::JoinHandle<()> {
+    thread::spawn(move || {
+        let mut next_tick = tick_interval.map(|interval| Instant::now() + interval);
+        while running.load(Ordering::SeqCst) {
+            let wait =
+                next_tick.map_or(IDLE_POLL, |at| at.saturating_duration_since(Instant::now()));
+            match event_rx.recv_timeout(wait) {
+                Ok(event) => {
+                    if let ServerEvent::Telemetry { tick, .. } = event {
+                        tick_counter.fetch_max(tick, Ordering::SeqCst);
+                    }
+                    broadcast(&subscribers, &event);
+                }
+                Err(mpsc::RecvTimeoutError::Timeout) => {
+                    let (Some(at), Some(interval)) = (next_tick.as_mut(), tick_interval) else {
+                        continue;
+                    };
+                    *at += interval;
+                    let tick = tick_counter.fetch_add(1, Ordering::SeqCst) + 1;
+                    let event = build_event(tick, seed, &entities);
+                    broadcast(&subscribers, &event);
+                }
+                Err(mpsc::RecvTimeoutError::Disconnected) => break,
+            }
+        }
+    })
+}
+
+/// Send `event` to every connection, dropping the ones that have closed.
+fn broadcast(subscribers: &Subscribers, event: &ServerEvent) {
+    let Ok(bytes) = wire::encode_frame(event) else {
+        return;
+    };
+    if let Ok(mut subscribers) = subscribers.lock() {
+        subscribers.retain(|writer| {
+            writer
+                .lock()
+                .is_ok_and(|mut stream| wire::write_frame(&mut *stream, &bytes).is_ok())
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table(ids: &[u64]) -> Arc<Mutex<BTreeMap<u64, EntityRecord>>> {
+        let map = ids
+            .iter()
+            .map(|&id| {
+                let record = EntityRecord {
+                    dimension: 0,
+                    entity_id: id,
+                    kind: format!("ship-{id}"),
+                    position: None,
+                    velocity: None,
+                    mass: None,
+                };
+                (id, record)
+            })
+            .collect();
+        Arc::new(Mutex::new(map))
+    }
+
+    fn telemetry(event: ServerEvent) -> (u64, String, String) {
+        match event {
+            ServerEvent::Telemetry {
+                id, ship, message, ..
+            } => (id, ship, message),
+            other => panic!("expected telemetry, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn seeded_telemetry_is_round_robin_and_reproducible() {
+        let first = table(&[3, 7]);
+        let second = table(&[3, 7]);
+        let events: Vec<_> = (1..=4)
+            .map(|tick| telemetry(build_event(tick, Some(42), &first)))
+            .collect();
+        let replay: Vec<_> = (1..=4)
+            .map(|tick| telemetry(build_event(tick, Some(42), &second)))
+            .collect();
+
+        let ids: Vec<_> = events.iter().map(|(id, _, _)| *id).collect();
+        assert_eq!(ids, [3, 7, 3, 7]);
+        assert_eq!(events[1].1, "ship-7");
+        assert_eq!(events, replay);
+        assert_ne!(events[0].2, events[2].2, "positions change per tick");
+
+        let other_seed = table(&[3, 7]);
+        assert_ne!(
+            telemetry(build_event(1, Some(43), &other_seed)).2,
+            events[0].2
+        );
+
+        let position = first.lock().unwrap()[&7].position;
+        let (x, y) = position.expect("telemetry moves the entity");
+        assert!((-100.0..100.0).contains(&x) && (-100.0..100.0).contains(&y));
+    }
+
+    #[test]
+    fn oversized_request_frame_stops_the_engine_with_an_error() {
+        use std::io::Write;
+
+        let (addr, handle) = FakeEngine::new()
+            .with_max_frame_size(64)
+            .spawn()
+            .expect("fake engine should start");
+        let mut stream = TcpStream::connect(addr).expect("connect");
+        stream
+            .write_all(&1024u32.to_be_bytes())
+            .expect("length prefix");
+
+        let err = handle.join().expect_err("the frame should be rejected");
+        assert_eq!(err.kind(), ErrorKind::InvalidData, "{err}");
+    }
+}
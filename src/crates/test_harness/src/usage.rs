@@ -0,0 +1,89 @@
+//! Sampling of the engine process's memory and CPU usage.
+
+use std::time::Duration;
+
+use crate::error::{HarnessError, HarnessResult};
+
+/// Resource usage of the engine process at the time it was sampled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResourceUsage {
+    /// Resident set size in bytes.
+    pub rss_bytes: u64,
+    /// User plus system CPU time consumed so far.
+    pub cpu_time: Duration,
+}
+
+/// Sample the usage of process `pid`.
+#[cfg(target_os = "linux")]
+pub(crate) fn sample(pid: u32) -> HarnessResult<ResourceUsage> {
+    let status = std::fs::read_to_string(format!("/proc/{pid}/status"))?;
+    let stat = std::fs::read_to_string(format!("/proc/{pid}/stat"))?;
+    let malformed = |file: &str| {
+        HarnessError::Io(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!("unexpected /proc/{pid}/{file} format"),
+        ))
+    };
+    Ok(ResourceUsage {
+        rss_bytes: parse_status_rss(&status).ok_or_else(|| malformed("status"))?,
+        cpu_time: parse_stat_cpu_time(&stat).ok_or_else(|| malformed("stat"))?,
+    })
+}
+
+/// Sample the usage of process `pid`.
+#[cfg(not(target_os = "linux"))]
+pub(crate) fn sample(_pid: u32) -> HarnessResult<ResourceUsage> {
+    Err(HarnessError::Unsupported(
+        "resource usage sampling on this platform".to_string(),
+    ))
+}
+
+/// Clock ticks per second used by `/proc/<pid>/stat` (`USER_HZ`), fixed by the
+/// Linux ABI on every mainstream architecture.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+const USER_HZ: u64 = 100;
+
+/// Resident set size from the `VmRSS:` line of `/proc/<pid>/status`.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_status_rss(status: &str) -> Option<u64> {
+    let line = status.lines().find(|line| line.starts_with("VmRSS:"))?;
+    let mut fields = line["VmRSS:".len()..].split_whitespace();
+    let value: u64 = fields.next()?.parse().ok()?;
+    match fields.next() {
+        Some("kB") | None => value.checked_mul(1024),
+        Some(_) => None,
+    }
+}
+
+/// `utime + stime` from `/proc/<pid>/stat`.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_stat_cpu_time(stat: &str) -> Option<Duration> {
+    // The command name may contain spaces and parentheses, so count fields
+    // from the last `)`; `utime` and `stime` are fields 14 and 15 overall.
+    let rest = &stat[stat.rfind(')')? + 1..];
+    let mut fields = rest.split_whitespace().skip(11);
+    let utime: u64 = fields.next()?.parse().ok()?;
+    let stime: u64 = fields.next()?.parse().ok()?;
+    let ticks = utime.checked_add(stime)?;
+    Some(
+        Duration::from_secs(ticks / USER_HZ)
+            + Duration::from_millis((ticks % USER_HZ) * 1000 / USER_HZ),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_proc_status_and_stat() {
+        let status = "Name:\tengine\nVmPeak:\t  9000 kB\nVmRSS:\t  2048 kB\nThreads:\t4\n";
+        assert_eq!(parse_status_rss(status), Some(2048 * 1024));
+        assert_eq!(parse_status_rss("Name:\tkthread\n"), None);
+
+        let stat = "4242 (phase (space) engine) S 1 4242 4242 0 -1 4194304 \
+                    100 0 0 0 250 37 0 0 20 0 4 0 1000 123456 512";
+        assert_eq!(parse_stat_cpu_time(stat), Some(Duration::from_millis(2870)));
+        assert_eq!(parse_stat_cpu_time("4242 (engine) S 1"), None);
+    }
+}
@@ -0,0 +1,55 @@
+//! Length-prefixed framing of protocol messages: a 4-byte big-endian length
+//! followed by an encoded `NetworkMessage` carrying a JSON payload.
+//!
+//! Shared by the request pipeline and the fake engine.
+
+use std::io::{self, ErrorKind, Read, Write};
+
+use phase_space_protocol::network::NetworkMessage;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+/// Largest frame accepted unless a caller asks for a different limit.
+pub(crate) const DEFAULT_MAX_FRAME_SIZE: usize = 16 * 1024 * 1024;
+
+/// Serialize `payload` into a complete frame, length prefix included.
+pub(crate) fn encode_frame<T: Serialize>(payload: &T) -> Result<Vec<u8>, serde_json::Error> {
+    let payload_bytes = serde_json::to_vec(payload)?;
+    let message = NetworkMessage::new(0, payload_bytes);
+    let bytes = message.to_bytes()?;
+
+    let mut framed = Vec::with_capacity(4 + bytes.len());
+    framed.extend_from_slice(&(bytes.len() as u32).to_be_bytes());
+    framed.extend_from_slice(&bytes);
+    Ok(framed)
+}
+
+/// Read the body of the next frame, rejecting lengths above `max_frame_size`
+/// with `InvalidData` before allocating for them.
+pub(crate) fn read_frame(stream: &mut impl Read, max_frame_size: usize) -> io::Result<Vec<u8>> {
+    let mut len_buf = [0u8; 4];
+    stream.read_exact(&mut len_buf)?;
+    let msg_len = u32::from_be_bytes(len_buf) as usize;
+    if msg_len > max_frame_size {
+        return Err(io::Error::new(
+            ErrorKind::InvalidData,
+            format!("frame of {msg_len} bytes exceeds the {max_frame_size} byte limit"),
+        ));
+    }
+    let mut msg_buf = vec![0u8; msg_len];
+    stream.read_exact(&mut msg_buf)?;
+    Ok(msg_buf)
+}
+
+/// Decode the JSON payload of a frame body returned by `read_frame`.
+pub(crate) fn decode_frame<T: DeserializeOwned>(frame: &[u8]) -> io::Result<T> {
+    let message = NetworkMessage::from_bytes(frame)
+        .map_err(|err| io::Error::new(ErrorKind::InvalidData, err.to_string()))?;
+    Ok(serde_json::from_slice(&message.payload)?)
+}
+
+/// Write a frame produced by `encode_frame` and flush it.
+pub(crate) fn write_frame(stream: &mut impl Write, framed: &[u8]) -> io::Result<()> {
+    stream.write_all(framed)?;
+    stream.flush()
+}
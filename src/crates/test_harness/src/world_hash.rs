@@ -0,0 +1,119 @@
+//! Helpers for the `tick N ... world_hash=<hash>` lines the engine emits for
+//! determinism checks.
+
+use std::cmp::Ordering;
+
+use crate::telemetry::TelemetryFields;
+
+/// Extract `(tick, hash)` from a log line, if it carries a world hash.
+///
+/// The tick is read from a `tick N` or `tick=N` token and the hash from a
+/// `world_hash=` token, as parsed by [`TelemetryFields`].
+pub fn parse_hash_line(line: &str) -> Option<(u64, String)> {
+    let fields = TelemetryFields::parse(line);
+    Some((
+        fields.get_u64("tick")?,
+        fields.get("world_hash")?.to_string(),
+    ))
+}
+
+/// Locate the first point where two `(tick, hash)` streams disagree.
+///
+/// Returns the tick together with the hash from `a` and from `b`, or `None` if
+/// one stream is a prefix of the other. When the streams report different
+/// ticks at the same position, the earlier tick is returned and the stream that
+/// skipped it gets an empty hash.
+pub fn first_divergence(a: &[(u64, String)], b: &[(u64, String)]) -> Option<(u64, String, String)> {
+    a.iter()
+        .zip(b)
+        .find(|(entry_a, entry_b)| entry_a != entry_b)
+        .map(
+            |((tick_a, hash_a), (tick_b, hash_b))| match tick_a.cmp(tick_b) {
+                Ordering::Equal => (*tick_a, hash_a.clone(), hash_b.clone()),
+                Ordering::Less => (*tick_a, hash_a.clone(), String::new()),
+                Ordering::Greater => (*tick_b, String::new(), hash_b.clone()),
+            },
+        )
+}
+
+/// Assert that two `(tick, hash)` streams are identical.
+///
+/// On mismatch the panic message names the first diverging tick and both
+/// hashes (e.g. `diverged at tick 4: abc != def`) instead of dumping both streams.
+#[track_caller]
+pub fn assert_streams_match(a: &[(u64, String)], b: &[(u64, String)]) {
+    if let Some((tick, hash_a, hash_b)) = first_divergence(a, b) {
+        let show = |hash: &str| {
+            if hash.is_empty() {
+                "<missing>".to_string()
+            } else {
+                hash.to_string()
+            }
+        };
+        panic!(
+            "world hash streams diverged at tick {tick}: {} != {}",
+            show(&hash_a),
+            show(&hash_b)
+        );
+    }
+    if a.len() != b.len() {
+        panic!(
+            "world hash streams differ in length: {} != {} (common prefix matches)",
+            a.len(),
+            b.len()
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{assert_streams_match, first_divergence, parse_hash_line};
+
+    #[test]
+    fn parses_tick_and_hash() {
+        assert_eq!(
+            parse_hash_line("tick 4 phase=done world_hash=abc123"),
+            Some((4, "abc123".to_string()))
+        );
+        assert_eq!(
+            parse_hash_line("entity 2 tick 9 [probe]: tick=9, world_hash=ff00,"),
+            Some((9, "ff00".to_string()))
+        );
+    }
+
+    #[test]
+    fn ignores_lines_without_both_fields() {
+        assert_eq!(parse_hash_line("tick 4 nothing to see"), None);
+        assert_eq!(parse_hash_line("world_hash=abc"), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "diverged at tick 4: abc != def")]
+    fn reports_first_diverging_tick() {
+        let a = vec![(3, "000".to_string()), (4, "abc".to_string())];
+        let b = vec![(3, "000".to_string()), (4, "def".to_string())];
+        assert_streams_match(&a, &a);
+        assert_streams_match(&a, &b);
+    }
+
+    #[test]
+    fn locates_first_divergence() {
+        let a = vec![
+            (1, "aa".to_string()),
+            (2, "bb".to_string()),
+            (3, "cc".to_string()),
+        ];
+        let b = vec![(1, "aa".to_string()), (2, "xx".to_string())];
+        assert_eq!(
+            first_divergence(&a, &b),
+            Some((2, "bb".to_string(), "xx".to_string()))
+        );
+        assert_eq!(first_divergence(&a, &a[..2]), None);
+
+        let skipped = vec![(1, "aa".to_string()), (3, "cc".to_string())];
+        assert_eq!(
+            first_divergence(&a, &skipped),
+            Some((2, "bb".to_string(), String::new()))
+        );
+    }
+}
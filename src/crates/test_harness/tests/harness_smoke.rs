@@ -1,9 +1,18 @@
 #![cfg(feature = "test-support")]
 
+use std::io::{BufRead, BufReader};
+use std::net::SocketAddr;
 use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::time::Duration;
 
-use phase_space_harness::{EngineConfig, EngineHarness, ScenarioConfig, SpawnSpec};
-use phase_space_protocol::psip::EntityParameters;
+use phase_space_harness::testing::{advance_manual_ticks, FakeEngine, FakeEngineConfig};
+use phase_space_harness::{
+    EngineConfig, EngineHarness, HarnessError, HarnessPool, LogLevel, LogStream, ScenarioConfig,
+    SpawnSpec,
+};
+use phase_space_protocol::psip::{EntityParameters, ResponseStatus, ServerEvent, ServerRequest};
+use regex::Regex;
 
 fn fake_engine_path() -> PathBuf {
     if let Ok(path) = std::env::var("CARGO_BIN_EXE_fake_engine") {
@@ -43,18 +52,728 @@ fn drives_fake_engine_end_to_end() {
         .expect("spawned entity present")
         .entity_id;
 
+    session
+        .record_history_for(&[entity_id])
+        .expect("entity is known");
     session.advance_ticks(3).expect("ticks should advance");
+    let history = session.telemetry_history(entity_id);
+    assert_eq!(history.len(), 1, "one sample per advance_ticks call");
+    assert_eq!(
+        session.trajectory(entity_id),
+        vec![(history[0].0, (0.0, 0.0))]
+    );
+    session
+        .assert_within_bounds(entity_id, (-1.0, -1.0), (1.0, 1.0))
+        .expect("probe stays at the origin");
+    let err = session
+        .assert_within_bounds(entity_id, (1.0, 1.0), (2.0, 2.0))
+        .expect_err("origin is outside the box");
+    assert!(matches!(err, HarnessError::OutOfBounds { tick, .. } if tick == history[0].0));
     let telemetry = session
         .telemetry_for(entity_id)
         .expect("inspect should succeed")
         .expect("entity should exist");
     assert_eq!(telemetry.entity_id, entity_id);
 
+    let late = session
+        .spawn(SpawnSpec::new("late_probe").in_dimension(1))
+        .expect("live spawn should succeed");
+    assert_eq!(session.entities().len(), 2);
+    assert!(session
+        .telemetry_for(late.entity_id)
+        .expect("inspect should succeed")
+        .is_some());
+
     let logs = session.logs_for(entity_id);
     assert!(
         !logs.is_empty(),
         "expected telemetry or logs for entity {entity_id}"
     );
 
+    let status = session
+        .shutdown()
+        .expect("shutdown should succeed")
+        .expect("spawned engine has an exit status");
+    assert!(status.success(), "engine should exit cleanly: {status}");
+}
+
+#[test]
+fn waits_for_absolute_tick() {
+    let harness =
+        EngineHarness::spawn(EngineConfig::new(fake_engine_path())).expect("engine should launch");
+    let mut session = harness
+        .run_scenario(ScenarioConfig::default())
+        .expect("scenario should start");
+
+    let observed = session
+        .wait_for_tick(5, Duration::from_secs(2))
+        .expect("tick should be reached");
+    assert!(observed >= 5, "observed tick {observed} below target");
+
+    let err = session
+        .wait_for_tick(u64::MAX, Duration::from_millis(50))
+        .expect_err("unreachable tick should time out");
+    assert!(matches!(err, HarnessError::TickTimeout { .. }));
+
+    session.shutdown().expect("shutdown should succeed");
+}
+
+#[test]
+fn waits_for_matching_events_once() {
+    let harness =
+        EngineHarness::spawn(EngineConfig::new(fake_engine_path())).expect("engine should launch");
+    let session = harness
+        .run_scenario(ScenarioConfig::default())
+        .expect("scenario should start");
+
+    let is_telemetry = |event: &ServerEvent| matches!(event, ServerEvent::Telemetry { .. });
+    let first = session
+        .wait_for_event(is_telemetry, Duration::from_secs(2))
+        .expect("telemetry should arrive");
+    let second = session
+        .wait_for_event(is_telemetry, Duration::from_secs(2))
+        .expect("more telemetry should arrive");
+
+    match (first, second) {
+        (ServerEvent::Telemetry { tick: a, .. }, ServerEvent::Telemetry { tick: b, .. }) => {
+            assert!(b > a, "second match should be a later event ({a} then {b})")
+        }
+        other => panic!("unexpected events: {other:?}"),
+    }
+
+    session.shutdown().expect("shutdown should succeed");
+}
+
+#[test]
+fn waits_for_startup_log_line() {
+    let harness =
+        EngineHarness::spawn(EngineConfig::new(fake_engine_path())).expect("engine should launch");
+    let session = harness
+        .run_scenario(ScenarioConfig::default())
+        .expect("scenario should start");
+
+    let line = session
+        .wait_for_log("listening on", Duration::from_secs(1))
+        .expect("banner should be captured");
+    assert_eq!(line.stream, LogStream::Stdout);
+
+    let pattern = Regex::new(r"listening on \S+").expect("valid regex");
+    session
+        .wait_for_log_matching(&pattern, Duration::from_millis(50))
+        .expect_err("banner should not be matched twice");
+    assert!(session.logs_as_string().contains("[STDOUT] listening on"));
+
+    session.shutdown().expect("shutdown should succeed");
+}
+
+#[test]
+fn custom_level_parser_classifies_lines() {
+    let config = EngineConfig::new(fake_engine_path())
+        .with_level_parser(|line| line.starts_with("listening").then_some(LogLevel::Info));
+    let session = EngineHarness::spawn(config)
+        .expect("engine should launch")
+        .run_scenario(ScenarioConfig::default())
+        .expect("scenario should start");
+
+    let info = session.logs_at_level(LogLevel::Info);
+    assert_eq!(info.len(), 1);
+    assert_eq!(info[0].stream, LogStream::Stdout);
+    assert!(session.logs_at_level(LogLevel::Error).is_empty());
+    session.assert_no_errors(&[]).expect("no error lines");
+
+    session.shutdown().expect("shutdown should succeed");
+}
+
+#[test]
+fn log_capacity_evicts_oldest_lines() {
+    let config = EngineConfig::new(fake_engine_path()).with_log_capacity(0);
+    let harness = EngineHarness::spawn(config).expect("engine should launch");
+    let session = harness
+        .run_scenario(ScenarioConfig::default())
+        .expect("scenario should start");
+
+    assert_eq!(session.dropped_log_count(), 1, "banner should be evicted");
+    assert!(session
+        .all_logs()
+        .iter()
+        .all(|line| line.stream == LogStream::Event));
+
+    session.shutdown().expect("shutdown should succeed");
+}
+
+#[test]
+fn event_capacity_keeps_most_recent_events() {
+    let config = EngineConfig::new(fake_engine_path()).with_event_capacity(4);
+    let harness = EngineHarness::spawn(config).expect("engine should launch");
+    let mut session = harness
+        .run_scenario(ScenarioConfig::default())
+        .expect("scenario should start");
+
+    session.advance_ticks(10).expect("ticks should advance");
+    assert_eq!(session.events().len(), 4);
+    assert!(session.dropped_event_count() > 0);
+    session
+        .wait_for_event(
+            |event| matches!(event, ServerEvent::Telemetry { .. }),
+            Duration::from_secs(1),
+        )
+        .expect("retained events should still be matched");
+
+    session.shutdown().expect("shutdown should succeed");
+}
+
+#[test]
+fn subscribers_receive_filtered_events() {
+    let harness =
+        EngineHarness::spawn(EngineConfig::new(fake_engine_path())).expect("engine should launch");
+    let session = harness
+        .run_scenario(ScenarioConfig::default())
+        .expect("scenario should start");
+
+    let telemetry =
+        session.subscribe_events(|event| matches!(event, ServerEvent::Telemetry { .. }));
+    let logs = session.subscribe_events(|event| matches!(event, ServerEvent::Log { .. }));
+
+    let event = telemetry
+        .recv_timeout(Duration::from_secs(2))
+        .expect("telemetry should be delivered");
+    assert!(matches!(event, ServerEvent::Telemetry { .. }));
+    assert!(logs.try_recv().is_err(), "fake engine emits no log events");
+
+    session.shutdown().expect("shutdown should succeed");
+}
+
+#[test]
+fn wait_for_exit_observes_natural_exit() {
+    let harness =
+        EngineHarness::spawn(EngineConfig::new(fake_engine_path())).expect("engine should launch");
+    let session = harness
+        .run_scenario(ScenarioConfig::default())
+        .expect("scenario should start");
+
+    // Stop the engine behind the harness's back, as a scenario end would.
+    session
+        .send(ServerRequest::Shutdown)
+        .expect("shutdown request should be answered");
+    let status = session
+        .wait_for_exit(Duration::from_secs(2))
+        .expect("engine should exit on its own")
+        .expect("spawned engine has an exit status");
+    assert!(status.success(), "engine should exit cleanly: {status}");
+}
+
+#[test]
+fn kill_terminates_without_graceful_shutdown() {
+    let harness =
+        EngineHarness::spawn(EngineConfig::new(fake_engine_path())).expect("engine should launch");
+    let session = harness
+        .run_scenario(ScenarioConfig::default())
+        .expect("scenario should start");
+
+    let status = session
+        .kill()
+        .expect("kill should succeed")
+        .expect("spawned engine has an exit status");
+    assert!(!status.success(), "killed engine should not report success");
+}
+
+#[cfg(unix)]
+#[test]
+fn auto_restart_relaunches_crashed_engine() {
+    let dir = tempfile::tempdir().expect("temp dir");
+    let log_path = dir.path().join("engine.log");
+    let config = EngineConfig::new(fake_engine_path())
+        .with_auto_restart(1)
+        .with_log_file(&log_path);
+    let mut session = EngineHarness::spawn(config)
+        .expect("engine should launch")
+        .run_scenario(ScenarioConfig::default().with_spawn(SpawnSpec::new("probe")))
+        .expect("scenario should start");
+    let first_pid = session.pid().expect("spawned engine has a pid");
+    let probe = session.entities()[0].entity_id;
+    session
+        .record_history_for(&[probe])
+        .expect("probe should be known");
+    let telemetry =
+        session.subscribe_events(|event| matches!(event, ServerEvent::Telemetry { .. }));
+
+    let killed = Command::new("kill")
+        .args(["-9", &first_pid.to_string()])
+        .status()
+        .expect("kill should run");
+    assert!(killed.success());
+
+    session
+        .advance_ticks(2)
+        .expect("engine should be relaunched");
+    assert_eq!(session.restart_count(), 1);
+    assert_ne!(session.pid(), Some(first_pid));
+    assert_eq!(session.entities().len(), 1, "scenario spawns are replayed");
+    // Whatever is queued may predate the crash; only the relaunched engine
+    // can produce telemetry from here on.
+    let _ = telemetry.try_iter().count();
+    session
+        .advance_ticks(1)
+        .expect("relaunched engine should tick");
+    assert!(
+        telemetry.recv_timeout(Duration::from_secs(5)).is_ok(),
+        "subscribers keep receiving events from the relaunched engine"
+    );
+    assert!(
+        !session.telemetry_history(probe).is_empty(),
+        "history registrations survive the restart"
+    );
+
+    let previous = session.previous_runs();
+    assert_eq!(previous.len(), 1);
+    assert!(!previous[0].status.success());
+    assert!(previous[0]
+        .logs
+        .iter()
+        .any(|line| line.line.contains("listening on")));
+    session.shutdown().expect("shutdown should succeed");
+
+    let logged = std::fs::read_to_string(&log_path).expect("log file should exist");
+    assert_eq!(
+        logged.matches("listening on").count(),
+        2,
+        "the relaunch appends to the log file"
+    );
+}
+
+#[test]
+fn fake_engine_exits_nonzero_on_oversized_frame() {
+    use std::io::Write;
+
+    let mut engine = Command::new(fake_engine_path())
+        .args(["--max-frame-size", "64"])
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("fake engine should start");
+    let mut banner = String::new();
+    BufReader::new(engine.stdout.take().expect("stdout is piped"))
+        .read_line(&mut banner)
+        .expect("banner should be printed");
+    let address: SocketAddr = banner
+        .trim()
+        .strip_prefix("listening on ")
+        .and_then(|addr| addr.parse().ok())
+        .expect("banner should contain the address");
+
+    let mut stream = std::net::TcpStream::connect(address).expect("engine should accept");
+    stream
+        .write_all(&1024u32.to_be_bytes())
+        .expect("length prefix should be sent");
+    let status = engine.wait().expect("engine should exit");
+    assert!(!status.success(), "an oversized frame must fail the engine");
+}
+
+#[test]
+fn connects_to_running_engine_without_owning_it() {
+    let mut engine = Command::new(fake_engine_path())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("fake engine should start");
+    let mut banner = String::new();
+    BufReader::new(engine.stdout.take().expect("stdout is piped"))
+        .read_line(&mut banner)
+        .expect("banner should be printed");
+    let address: SocketAddr = banner
+        .trim()
+        .strip_prefix("listening on ")
+        .and_then(|addr| addr.parse().ok())
+        .expect("banner should contain the address");
+
+    let mut session = EngineHarness::connect(address)
+        .expect("harness should connect")
+        .run_scenario(ScenarioConfig::default().with_spawn(SpawnSpec::new("probe")))
+        .expect("scenario should start");
+    assert_eq!(session.pid(), None);
+    session.advance_ticks(2).expect("ticks should advance");
+    drop(session);
+
+    assert!(
+        engine.try_wait().expect("engine status").is_none(),
+        "dropping the session must not stop a remote engine"
+    );
+    engine.kill().expect("engine should be killed");
+    engine.wait().expect("engine should be reaped");
+}
+
+#[test]
+fn drives_in_process_fake_engine() {
+    let (address, engine) = FakeEngine::with_config(FakeEngineConfig::default().manual_ticks())
+        .spawn()
+        .expect("fake engine should bind");
+    let mut session = EngineHarness::connect(address)
+        .expect("harness should connect")
+        .run_scenario(ScenarioConfig::default().with_spawn(SpawnSpec::new("probe")))
+        .expect("scenario should start");
+    advance_manual_ticks(&mut session, 2).expect("ticks should advance");
+
+    session.shutdown().expect("shutdown should succeed");
+    engine.join().expect("fake engine should stop cleanly");
+}
+
+#[test]
+fn fake_engine_injects_configured_faults() {
+    let config = FakeEngineConfig::default()
+        .with_spawn_failure_after(1)
+        .with_inspect_not_found(1);
+    let (address, _engine) = FakeEngine::with_config(config)
+        .spawn()
+        .expect("fake engine should bind");
+    let scenario = ScenarioConfig::default()
+        .continue_on_spawn_error(true)
+        .with_spawn(SpawnSpec::new("probe"))
+        .with_spawn(SpawnSpec::new("beacon"));
+    let session = EngineHarness::connect(address)
+        .expect("harness should connect")
+        .run_scenario(scenario)
+        .expect("scenario should start");
+
+    assert_eq!(session.entities().len(), 1);
+    assert_eq!(session.failed_spawns().len(), 1);
+    assert_eq!(session.failed_spawns()[0].0.entity_type, "beacon");
+    let (status, record) = session.inspect(1).expect("inspect should answer");
+    assert_eq!(status, ResponseStatus::NotFound);
+    assert!(record.is_none());
+
+    session.shutdown().expect("shutdown should succeed");
+}
+
+#[test]
+fn manual_ticks_advance_once_per_request() {
+    let (address, _engine) = FakeEngine::with_config(FakeEngineConfig::default().manual_ticks())
+        .spawn()
+        .expect("fake engine should bind");
+    let mut session = EngineHarness::connect(address)
+        .expect("harness should connect")
+        .run_scenario(ScenarioConfig::default().with_spawn(SpawnSpec::new("probe")))
+        .expect("scenario should start");
+
+    session.spawn(SpawnSpec::new("beacon")).expect("spawn");
+    session.spawn(SpawnSpec::new("beacon")).expect("spawn");
+    session
+        .wait_for_tick(3, Duration::from_secs(2))
+        .expect("one tick per spawn");
+    std::thread::sleep(Duration::from_millis(50));
+    assert_eq!(session.current_tick(), 3, "no ticks without requests");
+    advance_manual_ticks(&mut session, 2).expect("ticks should be requested");
+    assert_eq!(session.current_tick(), 5);
+
+    session.shutdown().expect("shutdown should succeed");
+}
+
+#[test]
+fn parses_seeded_telemetry_fields() {
+    let config = FakeEngineConfig::default().manual_ticks().with_seed(7);
+    let (address, _engine) = FakeEngine::with_config(config)
+        .spawn()
+        .expect("fake engine should bind");
+    let mut session = EngineHarness::connect(address)
+        .expect("harness should connect")
+        .run_scenario(ScenarioConfig::default().with_spawn(SpawnSpec::new("probe")))
+        .expect("scenario should start");
+    advance_manual_ticks(&mut session, 3).expect("ticks should advance");
+
+    let entity_id = session.entities()[0].entity_id;
+    let fields = session.telemetry_fields_for(entity_id);
+    assert!(!fields.is_empty());
+    for record in &fields {
+        assert!(record.get_u64("tick").is_some());
+        assert!(record.get_f64("x").is_some() && record.get_f64("y").is_some());
+    }
+
+    let stats = session.event_stats();
+    assert!(
+        stats.ships.contains("probe"),
+        "seeded telemetry is named by kind"
+    );
+    assert!(stats.telemetry_per_ship["probe"] >= fields.len());
+    assert_eq!(stats.log_events, 0);
+
+    session.shutdown().expect("shutdown should succeed");
+}
+
+#[test]
+fn waits_for_entity_dimension_and_all_ticks() {
+    let (address, _engine) = FakeEngine::with_config(FakeEngineConfig::default().with_seed(3))
+        .spawn()
+        .expect("fake engine should bind");
+    let scenario = ScenarioConfig::default()
+        .with_spawn(SpawnSpec::new("probe"))
+        .with_spawn(SpawnSpec::new("beacon").in_dimension(1));
+    let mut session = EngineHarness::connect(address)
+        .expect("harness should connect")
+        .run_scenario(scenario)
+        .expect("scenario should start");
+
+    let beacon = session.entities()[1].entity_id;
+    let reached = session
+        .wait_for_entity_tick(beacon, 6, Duration::from_secs(2))
+        .expect("beacon should reach tick 6");
+    assert!(reached >= 6);
+    assert!(session.entity_tick(beacon) >= Some(reached));
+    assert!(session
+        .entity_tick(session.entities()[0].entity_id)
+        .is_some());
+    assert_eq!(session.entity_tick(999), None);
+
+    session
+        .wait_for_dimension_tick(1, reached + 2, Duration::from_secs(2))
+        .expect("dimension 1 should keep ticking");
+    assert!(session.dimension_tick(1) >= Some(reached + 2));
+    assert_eq!(session.dimension_tick(7), None);
+
+    let lowest = session
+        .wait_for_all_ticks(reached + 4, Duration::from_secs(2))
+        .expect("every entity should catch up");
+    assert!(session
+        .entities()
+        .iter()
+        .all(|entity| session.entity_tick(entity.entity_id) >= Some(lowest)));
+
+    session.shutdown().expect("shutdown should succeed");
+}
+
+#[test]
+fn pipelined_spawns_preserve_order() {
+    let probes = SpawnSpec::new("probe").replicated(8, |index| EntityParameters {
+        position: Some((index as f64, 0.0)),
+        velocity: None,
+        mass: None,
+    });
+    let scenario = ScenarioConfig::default()
+        .with_pipelined_spawns(true)
+        .with_spawns_iter(probes);
+
+    let harness =
+        EngineHarness::spawn(EngineConfig::new(fake_engine_path())).expect("engine should launch");
+    let session = harness
+        .run_scenario(scenario)
+        .expect("scenario should start");
+
+    let positions: Vec<_> = session
+        .entities()
+        .iter()
+        .map(|entity| entity.position)
+        .collect();
+    let expected: Vec<_> = (0..8).map(|index| Some((index as f64, 0.0))).collect();
+    assert_eq!(positions, expected);
+
+    let mut ids: Vec<_> = session
+        .entities()
+        .iter()
+        .map(|entity| entity.entity_id)
+        .collect();
+    ids.reverse();
+    let inspected = session.inspect_many(&ids).expect("batch inspect");
+    let returned: Vec<_> = inspected.iter().map(|(id, _)| *id).collect();
+    assert_eq!(returned, ids, "results keep the requested order");
+    assert!(inspected
+        .iter()
+        .all(|(id, record)| record.as_ref().map(|record| record.entity_id) == Some(*id)));
+    assert!(matches!(
+        session.inspect_many(&[ids[0], 999]),
+        Err(HarnessError::UnknownEntity(999))
+    ));
+
+    session.shutdown().expect("shutdown should succeed");
+}
+
+#[test]
+fn scenario_loads_from_json_file() {
+    let file = tempfile::NamedTempFile::new().expect("temp file");
+    std::fs::write(
+        file.path(),
+        r#"{
+            "dt_seconds": 1.0,
+            "entities": [
+                { "name": "leader", "dimension": 0, "transform": { "x": 1.0, "y": 2.0, "z": 0.0 } },
+                { "name": "follower", "dimension": 1, "mass_kg": 900.0 }
+            ]
+        }"#,
+    )
+    .expect("scenario should be written");
+
+    let scenario = ScenarioConfig::from_json_file(file.path()).expect("scenario should parse");
+    assert_eq!(scenario.spawns.len(), 2);
+    assert_eq!(scenario.spawns[0].parameters.position, Some((1.0, 2.0)));
+    assert_eq!(scenario.spawns[1].dimension, Some(1));
+
+    let harness =
+        EngineHarness::spawn(EngineConfig::new(fake_engine_path())).expect("engine should launch");
+    let session = harness
+        .run_scenario(scenario)
+        .expect("scenario should start");
+    assert_eq!(session.entities()[1].kind, "follower");
+    assert_eq!(
+        session
+            .find_by_kind("leader")
+            .map(|entity| entity.dimension),
+        Some(0)
+    );
+    assert_eq!(session.find_all_by_kind("follower").len(), 1);
+    assert!(session.find_by_kind("missing").is_none());
+
+    session.shutdown().expect("shutdown should succeed");
+}
+
+#[test]
+fn bind_addr_rejects_conflicting_extra_arg() {
+    let config = EngineConfig::new(fake_engine_path())
+        .with_arg("--bind-addr=127.0.0.1:7100")
+        .with_bind_addr("127.0.0.1:7200".parse().expect("valid address"));
+
+    match EngineHarness::spawn(config) {
+        Err(HarnessError::EngineStart(message)) => assert!(message.contains("--bind-addr")),
+        Err(other) => panic!("unexpected error: {other}"),
+        Ok(_) => panic!("conflicting bind addresses should be rejected"),
+    }
+}
+
+#[test]
+fn bind_addr_waits_for_the_engine_to_listen() {
+    let addr = std::net::TcpListener::bind("127.0.0.1:0")
+        .and_then(|listener| listener.local_addr())
+        .expect("free port");
+    let config = EngineConfig::new(fake_engine_path()).with_bind_addr(addr);
+
+    let harness = EngineHarness::spawn(config).expect("engine should start on the bind address");
+    let session = harness
+        .run_scenario(ScenarioConfig::default().with_spawn(SpawnSpec::new("probe")))
+        .expect("scenario should start");
+    assert_eq!(session.entities().len(), 1);
+
+    session.shutdown().expect("shutdown should succeed");
+}
+
+#[test]
+fn startup_timeout_reports_recent_output() {
+    let config = EngineConfig::new(fake_engine_path())
+        .with_listen_pattern("never announced")
+        .with_startup_timeout(Duration::from_millis(300));
+
+    match EngineHarness::spawn(config) {
+        Err(HarnessError::StartupTimeout { recent_logs, .. }) => {
+            let address: SocketAddr = recent_logs
+                .iter()
+                .find_map(|line| line.split("listening on ").nth(1))
+                .and_then(|addr| addr.trim().parse().ok())
+                .expect("the banner should be among the recent logs");
+            assert!(
+                std::net::TcpStream::connect(address).is_err(),
+                "the engine must not outlive a failed startup"
+            );
+        }
+        Err(other) => panic!("unexpected error: {other}"),
+        Ok(_) => panic!("startup should time out"),
+    }
+}
+
+#[test]
+fn sweep_seeds_runs_one_engine_per_seed() {
+    let root = tempfile::tempdir().expect("temp dir");
+    let config = EngineConfig::new(fake_engine_path()).with_working_directory(root.path());
+    let scenario = ScenarioConfig::default().with_spawn(SpawnSpec::new("probe"));
+
+    let mut visited = Vec::new();
+    let results = EngineHarness::sweep_seeds(config, [7, 11], scenario, |session| {
+        visited.push(session.pid());
+        session.advance_ticks(1).expect("ticks should advance");
+    });
+
+    assert_eq!(visited.len(), 2);
+    assert_eq!(
+        results.iter().map(|(seed, _)| *seed).collect::<Vec<_>>(),
+        vec![7, 11]
+    );
+    assert!(results.iter().all(|(_, result)| result.is_ok()));
+    assert!(root.path().join("seed-7").is_dir());
+    assert!(root.path().join("seed-11").is_dir());
+}
+
+#[test]
+fn pool_spawns_independent_engines() {
+    let mut pool =
+        HarnessPool::spawn(EngineConfig::new(fake_engine_path()), 3).expect("pool should start");
+    assert_eq!(pool.len(), 3);
+
+    for session in pool.sessions_mut() {
+        session.advance_ticks(1).expect("ticks should advance");
+    }
+    let mut pids: Vec<u32> = pool
+        .sessions()
+        .iter()
+        .map(|session| session.pid().expect("spawned engine has a pid"))
+        .collect();
+    pids.sort_unstable();
+    pids.dedup();
+    assert_eq!(pids.len(), 3);
+
+    for result in pool.shutdown() {
+        let status = result.expect("shutdown should succeed");
+        assert!(status.is_some_and(|status| status.success()));
+    }
+}
+
+#[test]
+fn pool_reports_the_first_engine_that_failed_to_start() {
+    let config = EngineConfig::new("/nonexistent/phase-space-engine");
+
+    match HarnessPool::spawn(config, 2) {
+        Err(HarnessError::PoolSpawn { index, .. }) => assert_eq!(index, 0),
+        Err(other) => panic!("unexpected error: {other}"),
+        Ok(_) => panic!("a missing binary should fail the pool"),
+    }
+}
+
+#[test]
+fn keeps_log_lines_with_invalid_utf8() {
+    let config = EngineConfig::new(fake_engine_path()).with_arg("--emit-invalid-utf8");
+    let session = EngineHarness::spawn(config)
+        .expect("engine should launch")
+        .attach()
+        .expect("session should attach");
+
+    assert!(session
+        .stdout_logs()
+        .iter()
+        .any(|line| line.line == "plugin output \u{FFFD}\u{FFFD} done"));
+
+    session.shutdown().expect("shutdown should succeed");
+}
+
+#[test]
+fn parses_json_log_lines() {
+    let config = EngineConfig::new(fake_engine_path()).with_arg("--emit-json-log");
+    let session = EngineHarness::spawn(config)
+        .expect("engine should launch")
+        .attach()
+        .expect("session should attach");
+
+    let records = session.json_logs();
+    assert_eq!(records.len(), 1, "only the JSON line parses");
+    assert_eq!(records[0]["entity"].as_u64(), Some(1));
+    assert_eq!(records[0]["message"].as_str(), Some("plugin loaded"));
+
+    session.shutdown().expect("shutdown should succeed");
+}
+
+#[test]
+fn timeline_merges_streams_in_capture_order() {
+    let harness =
+        EngineHarness::spawn(EngineConfig::new(fake_engine_path())).expect("engine should launch");
+    let mut session = harness.attach().expect("session should attach");
+    session.advance_ticks(2).expect("ticks should advance");
+
+    let timeline = session.timeline();
+    assert!(timeline
+        .windows(2)
+        .all(|pair| (pair[0].captured_at, pair[0].seq) <= (pair[1].captured_at, pair[1].seq)));
+    assert_eq!(timeline[0].stream, LogStream::Stdout);
+    assert!(timeline.iter().any(|line| line.stream == LogStream::Event));
+
     session.shutdown().expect("shutdown should succeed");
 }